@@ -0,0 +1,9 @@
+DROP TRIGGER IF EXISTS comments_post_counters ON comments;
+DROP TRIGGER IF EXISTS reactions_post_counters ON reactions;
+DROP FUNCTION IF EXISTS comments_update_post_counters();
+DROP FUNCTION IF EXISTS reactions_update_post_counters();
+DROP INDEX IF EXISTS posts_created_at_idx;
+ALTER TABLE posts
+    DROP COLUMN IF EXISTS like_count,
+    DROP COLUMN IF EXISTS dislike_count,
+    DROP COLUMN IF EXISTS comment_count;
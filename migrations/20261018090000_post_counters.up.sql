@@ -0,0 +1,48 @@
+ALTER TABLE posts
+    ADD COLUMN like_count BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN dislike_count BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN comment_count BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX posts_created_at_idx ON posts (created_at DESC);
+
+CREATE OR REPLACE FUNCTION reactions_update_post_counters() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE posts SET
+            like_count = like_count - CASE WHEN OLD.reaction_type THEN 1 ELSE 0 END,
+            dislike_count = dislike_count - CASE WHEN OLD.reaction_type THEN 0 ELSE 1 END
+        WHERE id = OLD.post_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE posts SET
+            like_count = like_count + CASE WHEN NEW.reaction_type THEN 1 ELSE 0 END,
+            dislike_count = dislike_count + CASE WHEN NEW.reaction_type THEN 0 ELSE 1 END
+        WHERE id = NEW.post_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER reactions_post_counters
+AFTER INSERT OR UPDATE OF reaction_type, post_id OR DELETE ON reactions
+FOR EACH ROW EXECUTE FUNCTION reactions_update_post_counters();
+
+CREATE OR REPLACE FUNCTION comments_update_post_counters() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE posts SET comment_count = comment_count - 1 WHERE id = OLD.post_id;
+    ELSE
+        UPDATE posts SET comment_count = comment_count + 1 WHERE id = NEW.post_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER comments_post_counters
+AFTER INSERT OR DELETE ON comments
+FOR EACH ROW EXECUTE FUNCTION comments_update_post_counters();
+
+UPDATE posts SET
+    like_count = (SELECT COUNT(*) FROM reactions WHERE reactions.post_id = posts.id AND reactions.reaction_type = TRUE),
+    dislike_count = (SELECT COUNT(*) FROM reactions WHERE reactions.post_id = posts.id AND reactions.reaction_type = FALSE),
+    comment_count = (SELECT COUNT(*) FROM comments WHERE comments.post_id = posts.id);
@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS comment_mentions(UUID);
+DROP FUNCTION IF EXISTS post_mentions(UUID);
+DROP TABLE IF EXISTS mentions CASCADE;
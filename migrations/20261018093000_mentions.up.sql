@@ -0,0 +1,38 @@
+CREATE TABLE mentions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id UUID REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CHECK ((post_id IS NULL) <> (comment_id IS NULL))
+);
+
+CREATE INDEX mentions_user_id_idx ON mentions (user_id);
+CREATE INDEX mentions_post_id_idx ON mentions (post_id);
+CREATE INDEX mentions_comment_id_idx ON mentions (comment_id);
+
+CREATE OR REPLACE FUNCTION post_mentions(target UUID) RETURNS JSON AS $$
+    SELECT COALESCE(json_agg(json_build_object(
+        'user_id', mentions.user_id,
+        'username', users.username,
+        'start', mentions.start_offset,
+        'end', mentions.end_offset
+    ) ORDER BY mentions.start_offset), '[]'::JSON)
+    FROM mentions
+    JOIN users ON mentions.user_id = users.id
+    WHERE mentions.post_id = target;
+$$ LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION comment_mentions(target UUID) RETURNS JSON AS $$
+    SELECT COALESCE(json_agg(json_build_object(
+        'user_id', mentions.user_id,
+        'username', users.username,
+        'start', mentions.start_offset,
+        'end', mentions.end_offset
+    ) ORDER BY mentions.start_offset), '[]'::JSON)
+    FROM mentions
+    JOIN users ON mentions.user_id = users.id
+    WHERE mentions.comment_id = target;
+$$ LANGUAGE SQL STABLE;
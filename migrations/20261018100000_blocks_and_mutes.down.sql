@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS is_hidden_from(UUID, UUID);
+DROP TABLE IF EXISTS mutes CASCADE;
+DROP TABLE IF EXISTS blocks CASCADE;
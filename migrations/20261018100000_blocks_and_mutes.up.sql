@@ -0,0 +1,30 @@
+CREATE TABLE blocks (
+    blocker_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (blocker_id, blocked_id),
+    CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX blocks_blocked_id_idx ON blocks (blocked_id);
+
+CREATE TABLE mutes (
+    muter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    muted_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (muter_id, muted_id),
+    CHECK (muter_id <> muted_id)
+);
+
+-- Whether content written by `author` is hidden from `viewer`: either user
+-- blocked the other, or the viewer muted the author. Anonymous viewers see everything.
+CREATE OR REPLACE FUNCTION is_hidden_from(author UUID, viewer UUID) RETURNS BOOLEAN AS $$
+    SELECT viewer IS NOT NULL AND (
+        EXISTS (
+            SELECT 1 FROM blocks
+            WHERE (blocker_id = viewer AND blocked_id = author)
+                OR (blocker_id = author AND blocked_id = viewer)
+        )
+        OR EXISTS (SELECT 1 FROM mutes WHERE muter_id = viewer AND muted_id = author)
+    );
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,67 @@
+use crate::exports;
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    Argon2, PasswordHasher,
+};
+use sqlx::{Pool, Postgres};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+pub const ASSETS_DIR: &str = "./assets";
+
+/// Hashes a password with Argon2 and a fresh salt, in the PHC string format
+/// stored in `users.password`.
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+}
+
+/// Hard-deletes accounts whose deletion grace period has passed, along with
+/// their uploaded files and data exports. The database rows of their posts, comments,
+/// reactions and profile go with them through cascading foreign keys.
+/// Returns the number of deleted accounts.
+pub async fn purge_deleted_accounts(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let export_files: Vec<String> = sqlx::query_scalar!(
+        "SELECT data_exports.file_name FROM data_exports
+        JOIN users ON data_exports.user_id = users.id
+        WHERE users.delete_after <= NOW() AND data_exports.file_name IS NOT NULL"
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let user_ids: Vec<Uuid> =
+        sqlx::query_scalar!("DELETE FROM users WHERE delete_after <= NOW() RETURNING id")
+            .fetch_all(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+
+    for user_id in &user_ids {
+        remove_user_assets(*user_id).await;
+    }
+    for file_name in &export_files {
+        exports::remove_export_file(file_name).await;
+    }
+    Ok(user_ids.len() as u64)
+}
+
+/// Removes every profile picture uploaded by a user. Uploads are stored as
+/// `<user id>.<extension>`, so older uploads with another extension are
+/// cleaned up as well.
+pub async fn remove_user_assets(user_id: Uuid) {
+    for extension in ["png", "jpg"] {
+        let path = PathBuf::from(ASSETS_DIR).join(format!("{}.{}", user_id, extension));
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => tracing::warn!("failed to remove {}: {:?}", path.display(), err),
+        }
+    }
+}
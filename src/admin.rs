@@ -0,0 +1,196 @@
+use crate::accounts::{self, ASSETS_DIR};
+use sqlx::{Pool, Postgres};
+use std::{collections::HashSet, path::PathBuf, time::Duration};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const ROLES: [&str; 3] = ["user", "moderator", "admin"];
+
+/// Files modified more recently than this are left alone by the asset purge.
+/// An upload writes its file before pointing the profile at it, so a new file
+/// can look orphaned for a moment.
+const ASSET_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Error)]
+pub enum AdminError {
+    #[error("user {0} does not exist")]
+    UnknownUser(String),
+    #[error("{0} does not exist")]
+    NotFound(Uuid),
+    #[error("unknown role {0}, expected one of user, moderator or admin")]
+    UnknownRole(String),
+    #[error("failed to hash the password: {0}")]
+    Hash(argon2::password_hash::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Creates a user and their profile the same way registration does, with the
+/// given role.
+pub async fn create_user(
+    db: &Pool<Postgres>,
+    username: &str,
+    email: &str,
+    password: &str,
+    role: &str,
+) -> Result<Uuid, AdminError> {
+    check_role(role)?;
+    let hashed_password = accounts::hash_password(password).map_err(AdminError::Hash)?;
+
+    let mut tx = db.begin().await?;
+    let user_id = sqlx::query_scalar!(
+        "INSERT INTO users (username, email, password, role) VALUES ($1, $2, $3, $4) RETURNING id",
+        username,
+        email.to_ascii_lowercase(),
+        hashed_password,
+        role
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO profiles (user_id, profile_image, bio) VALUES ($1, $2, $3)",
+        user_id,
+        "default.jpg",
+        ""
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(user_id)
+}
+
+pub async fn reset_password(
+    db: &Pool<Postgres>,
+    username: &str,
+    password: &str,
+) -> Result<(), AdminError> {
+    let hashed_password = accounts::hash_password(password).map_err(AdminError::Hash)?;
+    let updated = sqlx::query!(
+        "UPDATE users SET password = $1, updated_at = NOW() WHERE username = $2",
+        hashed_password,
+        username
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+    expect_user(updated, username)
+}
+
+pub async fn set_role(db: &Pool<Postgres>, username: &str, role: &str) -> Result<(), AdminError> {
+    check_role(role)?;
+    let updated = sqlx::query!(
+        "UPDATE users SET role = $1, updated_at = NOW() WHERE username = $2",
+        role,
+        username
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+    expect_user(updated, username)
+}
+
+/// Bans or unbans a user. Banned users cannot log in, and their existing
+/// sessions are rejected by the auth middleware.
+pub async fn set_banned(
+    db: &Pool<Postgres>,
+    username: &str,
+    banned: bool,
+) -> Result<(), AdminError> {
+    let updated = sqlx::query!(
+        "UPDATE users SET banned_at = CASE WHEN $1 THEN COALESCE(banned_at, NOW()) END
+        WHERE username = $2",
+        banned,
+        username
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+    expect_user(updated, username)
+}
+
+/// Soft-deletes a post, as a moderator would. It stays restorable until the
+/// retention period passes.
+pub async fn delete_post(db: &Pool<Postgres>, post_id: Uuid) -> Result<(), AdminError> {
+    let deleted = sqlx::query!(
+        "UPDATE posts SET deleted_at = NOW(), deleted_by = NULL
+        WHERE id = $1 AND deleted_at IS NULL",
+        post_id
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+    expect_row(deleted, post_id)
+}
+
+pub async fn delete_comment(db: &Pool<Postgres>, comment_id: Uuid) -> Result<(), AdminError> {
+    let deleted = sqlx::query!("DELETE FROM comments WHERE id = $1", comment_id)
+        .execute(db)
+        .await?
+        .rows_affected();
+    expect_row(deleted, comment_id)
+}
+
+/// Finds files in the assets directory that no profile points to, such as
+/// an older upload with another extension, and removes them unless
+/// `dry_run` is set. The default avatars and files modified in the last few
+/// minutes are always kept. Returns the orphaned paths.
+pub async fn purge_orphaned_assets(
+    db: &Pool<Postgres>,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, AdminError> {
+    let referenced: HashSet<String> =
+        sqlx::query_scalar!("SELECT DISTINCT profile_image FROM profiles")
+            .fetch_all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+    let mut orphans = Vec::new();
+    let mut entries = tokio::fs::read_dir(ASSETS_DIR).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let age = metadata.modified()?.elapsed().unwrap_or_default();
+        if age < ASSET_GRACE_PERIOD {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name.starts_with("default.") || referenced.contains(&file_name) {
+            continue;
+        }
+        if !dry_run {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+        orphans.push(entry.path());
+    }
+    orphans.sort();
+    Ok(orphans)
+}
+
+fn check_role(role: &str) -> Result<(), AdminError> {
+    if ROLES.contains(&role) {
+        Ok(())
+    } else {
+        Err(AdminError::UnknownRole(role.to_string()))
+    }
+}
+
+fn expect_user(updated: u64, username: &str) -> Result<(), AdminError> {
+    if updated == 0 {
+        return Err(AdminError::UnknownUser(username.to_string()));
+    }
+    Ok(())
+}
+
+fn expect_row(updated: u64, id: Uuid) -> Result<(), AdminError> {
+    if updated == 0 {
+        return Err(AdminError::NotFound(id));
+    }
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use clap::{Parser, Subcommand};
+use dotenv::dotenv;
+use server::{
+    admin, commands,
+    config::{Config, Profile},
+    schema::RegisterUserSchema,
+    seed::{self, SeedOptions},
+    validation::{validate_password_length, ValidationLimits},
+};
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use std::{
+    error::Error,
+    io::{BufRead, Write},
+};
+use uuid::Uuid;
+use validator::ValidateArgs;
+
+/// Administrative tasks against the database the server is configured for.
+#[derive(Parser)]
+#[command(name = "blaze-admin")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a user with a profile. The password is read from stdin unless given.
+    CreateUser {
+        username: String,
+        email: String,
+        #[arg(long, default_value = "user")]
+        role: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password. The password is read from stdin unless given.
+    ResetPassword {
+        username: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Change a user's role to user, moderator or admin.
+    SetRole { username: String, role: String },
+    /// Block a user from logging in; requests on their existing sessions are rejected.
+    Ban { username: String },
+    /// Let a banned user log in again.
+    Unban { username: String },
+    /// Soft-delete a post; it can be restored until the retention period ends.
+    DeletePost { post_id: Uuid },
+    /// Permanently delete a comment.
+    DeleteComment { comment_id: Uuid },
+    /// Recompute post like, dislike and comment counters.
+    RepairCounters,
+    /// Remove files in ./assets that no profile points to, except recent uploads.
+    PurgeAssets {
+        /// Only list the files that would be removed.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Fill a development database with fake users, posts, comments and reactions.
+    Seed {
+        /// The same seed always generates the same data, apart from timestamps.
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        #[arg(long, default_value_t = 50)]
+        users: usize,
+        #[arg(long, default_value_t = 200)]
+        posts: usize,
+        /// Average number of comments per post.
+        #[arg(long, default_value_t = 5)]
+        comments_per_post: usize,
+        /// Average number of reactions per post.
+        #[arg(long, default_value_t = 10)]
+        reactions_per_post: usize,
+        /// Password of every generated user.
+        #[arg(long, default_value = "password123")]
+        password: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+    let cli = Cli::parse();
+
+    let config = match Config::init() {
+        Ok(config) => config,
+        Err(err) => {
+            println!("🔥 Invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let pool = match PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&config.database_url)
+        .await
+    {
+        Ok(pool) => pool,
+        Err(err) => {
+            println!("🔥 Failed to connect to the database: {:?}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if matches!(cli.command, Command::Seed { .. }) && config.profile == Profile::Prod {
+        println!("🔥 Refusing to seed a database in the prod profile");
+        std::process::exit(1);
+    }
+
+    match run(&pool, &config.limits, cli.command).await {
+        Ok(message) => println!("✅ {}", message),
+        Err(err) => {
+            println!("🔥 {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn run(
+    pool: &Pool<Postgres>,
+    limits: &ValidationLimits,
+    command: Command,
+) -> Result<String, Box<dyn Error>> {
+    match command {
+        Command::CreateUser {
+            username,
+            email,
+            role,
+            password,
+        } => {
+            let password = password.unwrap_or_else(read_password);
+            let schema = RegisterUserSchema {
+                username,
+                email,
+                password,
+            };
+            if let Err(errors) = schema.validate_with_args(limits) {
+                invalid(errors);
+            }
+            let user_id = admin::create_user(
+                pool,
+                &schema.username,
+                &schema.email,
+                &schema.password,
+                &role,
+            )
+            .await?;
+            Ok(format!("Created {} ({})", schema.username, user_id))
+        }
+        Command::ResetPassword { username, password } => {
+            let password = password.unwrap_or_else(read_password);
+            if let Err(error) = validate_password_length(&password, limits) {
+                invalid(error);
+            }
+            admin::reset_password(pool, &username, &password).await?;
+            Ok(format!("Reset the password of {}", username))
+        }
+        Command::SetRole { username, role } => {
+            admin::set_role(pool, &username, &role).await?;
+            Ok(format!("{} is now {}", username, role))
+        }
+        Command::Ban { username } => {
+            admin::set_banned(pool, &username, true).await?;
+            Ok(format!("Banned {}", username))
+        }
+        Command::Unban { username } => {
+            admin::set_banned(pool, &username, false).await?;
+            Ok(format!("Unbanned {}", username))
+        }
+        Command::DeletePost { post_id } => {
+            admin::delete_post(pool, post_id).await?;
+            Ok(format!("Deleted post {}", post_id))
+        }
+        Command::DeleteComment { comment_id } => {
+            admin::delete_comment(pool, comment_id).await?;
+            Ok(format!("Deleted comment {}", comment_id))
+        }
+        Command::RepairCounters => {
+            let repaired = commands::repair_counters(pool).await?;
+            Ok(format!("Repaired counters on {} posts", repaired))
+        }
+        Command::PurgeAssets { dry_run } => {
+            let orphans = admin::purge_orphaned_assets(pool, dry_run).await?;
+            for path in &orphans {
+                println!("{}", path.display());
+            }
+            let verb = if dry_run { "Found" } else { "Removed" };
+            Ok(format!("{} {} orphaned files", verb, orphans.len()))
+        }
+        Command::Seed {
+            seed,
+            users,
+            posts,
+            comments_per_post,
+            reactions_per_post,
+            password,
+        } => {
+            let summary = seed::seed(
+                pool,
+                &SeedOptions {
+                    seed,
+                    users,
+                    posts,
+                    comments_per_post,
+                    reactions_per_post,
+                    password,
+                },
+            )
+            .await?;
+            Ok(format!(
+                "Seeded {} users, {} posts, {} comments and {} reactions",
+                summary.users, summary.posts, summary.comments, summary.reactions
+            ))
+        }
+    }
+}
+
+fn read_password() -> String {
+    print!("Password: ");
+    let _ = std::io::stdout().flush();
+    let mut password = String::new();
+    if let Err(err) = std::io::stdin().lock().read_line(&mut password) {
+        println!("🔥 Failed to read the password: {}", err);
+        std::process::exit(1);
+    }
+    password.trim_end_matches(['\r', '\n']).to_string()
+}
+
+fn invalid(errors: impl std::fmt::Display) -> ! {
+    println!("🔥 {}", errors);
+    std::process::exit(1);
+}
@@ -0,0 +1,63 @@
+use sqlx::PgExecutor;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Returns whether either user has blocked the other.
+pub async fn is_blocked_between<'e>(
+    db: impl PgExecutor<'e>,
+    user_id: Uuid,
+    other_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let blocked = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1 FROM blocks
+            WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+        )",
+        user_id,
+        other_id
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or(false);
+    Ok(blocked)
+}
+
+/// Returns whether the user has blocked, or been blocked by, any other member
+/// of a conversation.
+pub async fn is_blocked_in_conversation<'e>(
+    db: impl PgExecutor<'e>,
+    conversation_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let blocked = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1 FROM conversation_members
+            JOIN blocks ON (blocks.blocker_id = conversation_members.user_id AND blocks.blocked_id = $2)
+                OR (blocks.blocker_id = $2 AND blocks.blocked_id = conversation_members.user_id)
+            WHERE conversation_members.conversation_id = $1 AND conversation_members.user_id <> $2
+        )",
+        conversation_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or(false);
+    Ok(blocked)
+}
+
+/// The users whose content is hidden from the viewer, mirroring the SQL
+/// `is_hidden_from`: users either side has blocked and users the viewer muted.
+pub async fn hidden_from<'e>(
+    db: impl PgExecutor<'e>,
+    viewer_id: Uuid,
+) -> Result<HashSet<Uuid>, sqlx::Error> {
+    let users = sqlx::query_scalar!(
+        r#"SELECT blocked_id AS "user_id!" FROM blocks WHERE blocker_id = $1
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id = $1
+        UNION SELECT muted_id FROM mutes WHERE muter_id = $1"#,
+        viewer_id
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(users.into_iter().collect())
+}
@@ -0,0 +1,37 @@
+use sqlx::{Pool, Postgres};
+
+/// Recomputes the denormalized like, dislike and comment counters on `posts`
+/// from the `reactions` and `comments` tables. Returns the number of posts
+/// whose counters had drifted and were corrected.
+pub async fn repair_counters(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // Block writers while recounting so the triggers cannot race the repair.
+    sqlx::query!("LOCK TABLE reactions, comments IN SHARE MODE")
+        .execute(&mut *tx)
+        .await?;
+
+    let repaired = sqlx::query!(
+        "UPDATE posts SET
+            like_count = counts.likes,
+            dislike_count = counts.dislikes,
+            comment_count = counts.comments
+        FROM (
+            SELECT
+                posts.id,
+                (SELECT COUNT(*) FROM reactions WHERE reactions.post_id = posts.id AND reactions.reaction_type = TRUE) AS likes,
+                (SELECT COUNT(*) FROM reactions WHERE reactions.post_id = posts.id AND reactions.reaction_type = FALSE) AS dislikes,
+                (SELECT COUNT(*) FROM comments WHERE comments.post_id = posts.id) AS comments
+            FROM posts
+        ) AS counts
+        WHERE posts.id = counts.id
+            AND (posts.like_count, posts.dislike_count, posts.comment_count)
+                IS DISTINCT FROM (counts.likes, counts.dislikes, counts.comments)"
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(repaired)
+}
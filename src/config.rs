@@ -1,22 +1,408 @@
-#[derive(Debug,Clone)]
+use crate::validation::{Limit, ValidationLimits};
+use std::{fmt::Display, net::SocketAddr, path::Path, str::FromStr};
+use thiserror::Error;
+use tower_sessions_redis_store::fred::prelude::RedisConfig;
+use tracing::Level;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{0} must be set")]
+    Missing(&'static str),
+    #[error("{key} is invalid: {reason}")]
+    Invalid { key: &'static str, reason: String },
+    #[error("failed to read config file {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to parse config file {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Dev,
+    Prod,
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        match profile {
+            "dev" => Ok(Profile::Dev),
+            "prod" => Ok(Profile::Prod),
+            _ => Err("expected dev or prod".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Config {
+    pub profile: Profile,
+    pub bind_address: SocketAddr,
     pub database_url: String,
+    pub database_max_connections: u32,
+    pub run_migrations: bool,
+    pub redis_url: String,
+    pub redis_pool_size: usize,
+    pub cors_origins: Vec<String>,
+    pub session_secure: bool,
+    pub session_expiry_hours: i64,
+    pub log_level: Level,
+    pub shutdown_timeout_secs: u64,
+    pub shutdown_readiness_delay_secs: u64,
+    pub metrics_token: Option<String>,
+    pub metrics_bind_address: Option<SocketAddr>,
+    pub account_deletion_grace_days: i32,
+    pub post_retention_days: i32,
+    pub legacy_routes: bool,
+    pub legacy_routes_sunset: Option<String>,
+    pub limits: ValidationLimits,
     //pub jwt_secret: String,
     //pub jwt_expires_in: String,
     //pub jwt_maxage: i32,
 }
 
 impl Config {
-    pub fn init() -> Config {
-        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        //let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-        //let jwt_expires_in = std::env::var("JWT_EXPIRED_IN").expect("JWT_EXPIRED_IN must be set");
-        //let jwt_maxage = std::env::var("JWT_MAXAGE").expect("JWT_MAXAGE must be set");
-        Config {
-            database_url,
-            //jwt_secret,
-            //jwt_expires_in,
-            //sjwt_maxage: jwt_maxage.parse::<i32>().unwrap(),
+    /// Loads the configuration from the environment and an optional TOML
+    /// file, named by `CONFIG_FILE` or `config.toml` in the working directory.
+    /// Environment variables take precedence over the file, which takes
+    /// precedence over the defaults of the selected `APP_PROFILE`.
+    pub fn init() -> Result<Config, ConfigError> {
+        let path = std::env::var("CONFIG_FILE").ok();
+        let file = match &path {
+            Some(path) => read_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => read_file(DEFAULT_CONFIG_FILE)?,
+            None => toml::Table::new(),
+        };
+        Config::load(|key| std::env::var(key).ok(), file)
+    }
+
+    /// Builds the configuration from a variable lookup and the contents of a
+    /// config file, whose keys are the lowercased variable names.
+    pub fn load(
+        env: impl Fn(&str) -> Option<String>,
+        file: toml::Table,
+    ) -> Result<Config, ConfigError> {
+        let source = Source { env: &env, file };
+
+        let profile = source.parse("APP_PROFILE", Profile::Dev)?;
+        let dev = profile == Profile::Dev;
+
+        let config = Config {
+            profile,
+            bind_address: source.parse("BIND_ADDRESS", SocketAddr::from(([0, 0, 0, 0], 8000)))?,
+            database_url: source
+                .get("DATABASE_URL")
+                .ok_or(ConfigError::Missing("DATABASE_URL"))?,
+            database_max_connections: source.parse("DATABASE_MAX_CONNECTIONS", 10)?,
+            run_migrations: source.parse("RUN_MIGRATIONS", false)?,
+            redis_url: source
+                .get("REDIS_URL")
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string()),
+            redis_pool_size: source.parse("REDIS_POOL_SIZE", 6)?,
+            cors_origins: match source.get("CORS_ORIGINS") {
+                Some(origins) => origins
+                    .split(',')
+                    .map(|origin| origin.trim().to_string())
+                    .filter(|origin| !origin.is_empty())
+                    .collect(),
+                None if dev => vec!["http://localhost:5173".to_string()],
+                None => Vec::new(),
+            },
+            session_secure: source.parse("SESSION_SECURE", !dev)?,
+            session_expiry_hours: source.parse("SESSION_EXPIRY_HOURS", 12)?,
+            log_level: source.parse("LOG_LEVEL", if dev { Level::DEBUG } else { Level::INFO })?,
+            shutdown_timeout_secs: source.parse("SHUTDOWN_TIMEOUT_SECS", 30)?,
+            shutdown_readiness_delay_secs: source
+                .parse("SHUTDOWN_READINESS_DELAY_SECS", if dev { 0 } else { 5 })?,
+            metrics_token: source.get("METRICS_TOKEN"),
+            metrics_bind_address: source.parse_optional("METRICS_BIND_ADDRESS")?,
+            account_deletion_grace_days: source.parse("ACCOUNT_DELETION_GRACE_DAYS", 30)?,
+            post_retention_days: source.parse("POST_RETENTION_DAYS", 30)?,
+            legacy_routes: source.parse("LEGACY_ROUTES", true)?,
+            legacy_routes_sunset: source.get("LEGACY_ROUTES_SUNSET"),
+            limits: source.limits()?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.database_max_connections == 0 {
+            return Err(invalid("DATABASE_MAX_CONNECTIONS", "must be at least 1"));
+        }
+        if self.redis_pool_size == 0 {
+            return Err(invalid("REDIS_POOL_SIZE", "must be at least 1"));
+        }
+        if let Err(err) = RedisConfig::from_url(&self.redis_url) {
+            return Err(invalid("REDIS_URL", err));
+        }
+        if self.cors_origins.is_empty() {
+            return Err(ConfigError::Missing("CORS_ORIGINS"));
+        }
+        if let Some(origin) = self
+            .cors_origins
+            .iter()
+            .find(|origin| !origin.starts_with("http://") && !origin.starts_with("https://"))
+        {
+            return Err(invalid(
+                "CORS_ORIGINS",
+                format!("{} is not an http(s) origin", origin),
+            ));
+        }
+        if self.session_expiry_hours < 1 {
+            return Err(invalid("SESSION_EXPIRY_HOURS", "must be at least 1"));
+        }
+        if self.shutdown_timeout_secs == 0 {
+            return Err(invalid("SHUTDOWN_TIMEOUT_SECS", "must be at least 1"));
+        }
+        if self
+            .metrics_token
+            .as_ref()
+            .is_some_and(|token| token.len() < 16)
+        {
+            return Err(invalid("METRICS_TOKEN", "must be at least 16 characters"));
+        }
+        if self.metrics_bind_address == Some(self.bind_address) {
+            return Err(invalid(
+                "METRICS_BIND_ADDRESS",
+                "must differ from BIND_ADDRESS",
+            ));
+        }
+        if self.account_deletion_grace_days < 0 {
+            return Err(invalid(
+                "ACCOUNT_DELETION_GRACE_DAYS",
+                "must not be negative",
+            ));
+        }
+        if self.post_retention_days < 1 {
+            return Err(invalid("POST_RETENTION_DAYS", "must be at least 1"));
+        }
+        for (min_key, max_key, limit) in self.limits.named() {
+            if limit.min < 1 {
+                return Err(invalid(min_key, "must be at least 1"));
+            }
+            if limit.max < limit.min {
+                return Err(invalid(max_key, format!("must be at least {}", limit.min)));
+            }
+            if let Some(column) = limit.column.filter(|&column| limit.max > column) {
+                return Err(invalid(
+                    max_key,
+                    format!("must be at most {} to fit the database column", column),
+                ));
+            }
+        }
+        if self.profile == Profile::Prod && !self.session_secure {
+            return Err(invalid(
+                "SESSION_SECURE",
+                "session cookies must be secure in the prod profile",
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct Source<'a> {
+    env: &'a dyn Fn(&str) -> Option<String>,
+    file: toml::Table,
+}
+
+impl Source<'_> {
+    fn get(&self, key: &'static str) -> Option<String> {
+        if let Some(value) = (self.env)(key) {
+            return Some(value);
+        }
+        match self.file.get(&key.to_lowercase())? {
+            toml::Value::String(value) => Some(value.clone()),
+            toml::Value::Array(values) => Some(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        toml::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            value => Some(value.to_string()),
         }
     }
+
+    fn parse<T>(&self, key: &'static str, default: T) -> Result<T, ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get(key) {
+            Some(value) => value.trim().parse().map_err(|err| invalid(key, err)),
+            None => Ok(default),
+        }
+    }
+
+    fn limits(&self) -> Result<ValidationLimits, ConfigError> {
+        let [username, email, password, title, content, collection, message] =
+            ValidationLimits::default()
+                .named()
+                .map(|(min_key, max_key, default)| {
+                    Ok(Limit {
+                        min: self.parse(min_key, default.min)?,
+                        max: self.parse(max_key, default.max)?,
+                        ..default
+                    })
+                });
+        Ok(ValidationLimits {
+            username: username?,
+            email: email?,
+            password: password?,
+            title: title?,
+            content: content?,
+            collection: collection?,
+            message: message?,
+        })
+    }
+
+    fn parse_optional<T>(&self, key: &'static str) -> Result<Option<T>, ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key)
+            .map(|value| value.trim().parse().map_err(|err| invalid(key, err)))
+            .transpose()
+    }
+}
+
+impl ValidationLimits {
+    /// Every limit with its configuration keys.
+    fn named(&self) -> [(&'static str, &'static str, Limit); 7] {
+        [
+            ("USERNAME_MIN_LENGTH", "USERNAME_MAX_LENGTH", self.username),
+            ("EMAIL_MIN_LENGTH", "EMAIL_MAX_LENGTH", self.email),
+            ("PASSWORD_MIN_LENGTH", "PASSWORD_MAX_LENGTH", self.password),
+            ("TITLE_MIN_LENGTH", "TITLE_MAX_LENGTH", self.title),
+            ("CONTENT_MIN_LENGTH", "CONTENT_MAX_LENGTH", self.content),
+            (
+                "COLLECTION_MIN_LENGTH",
+                "COLLECTION_MAX_LENGTH",
+                self.collection,
+            ),
+            ("MESSAGE_MIN_LENGTH", "MESSAGE_MAX_LENGTH", self.message),
+        ]
+    }
+}
+
+fn invalid(key: &'static str, reason: impl Display) -> ConfigError {
+    ConfigError::Invalid {
+        key,
+        reason: reason.to_string(),
+    }
+}
+
+fn read_file(path: &str) -> Result<toml::Table, ConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+        path: path.to_string(),
+        source,
+    })?;
+    contents.parse().map_err(|source| ConfigError::Parse {
+        path: path.to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigError, Profile};
+    use std::collections::HashMap;
+    use tracing::Level;
+
+    const DATABASE_URL: &str = "postgres://localhost/blaze";
+
+    fn load(env: &[(&str, &str)], file: &str) -> Result<Config, ConfigError> {
+        let mut env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        env.entry("DATABASE_URL".to_string())
+            .or_insert_with(|| DATABASE_URL.to_string());
+        Config::load(|key| env.get(key).cloned(), file.parse().unwrap())
+    }
+
+    fn invalid_key(result: Result<Config, ConfigError>) -> &'static str {
+        match result {
+            Err(ConfigError::Invalid { key, .. }) => key,
+            other => panic!("expected an invalid key, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults_to_the_dev_profile() {
+        let config = load(&[], "").unwrap();
+        assert_eq!(config.profile, Profile::Dev);
+        assert_eq!(config.bind_address.to_string(), "0.0.0.0:8000");
+        assert_eq!(config.database_url, DATABASE_URL);
+        assert_eq!(config.cors_origins, ["http://localhost:5173"]);
+        assert!(!config.session_secure);
+        assert_eq!(config.log_level, Level::DEBUG);
+        assert_eq!(config.shutdown_readiness_delay_secs, 0);
+        assert!(config.legacy_routes);
+        assert_eq!(config.limits.username.max, 20);
+    }
+
+    #[test]
+    fn requires_a_database_url() {
+        let result = Config::load(|_| None, toml::Table::new());
+        assert!(matches!(result, Err(ConfigError::Missing("DATABASE_URL"))));
+    }
+
+    #[test]
+    fn the_environment_overrides_the_file() {
+        let file = r#"
+            bind_address = "127.0.0.1:9000"
+            log_level = "warn"
+            cors_origins = ["https://a.example", "https://b.example"]
+        "#;
+        let config = load(&[("LOG_LEVEL", "error")], file).unwrap();
+        assert_eq!(config.bind_address.to_string(), "127.0.0.1:9000");
+        assert_eq!(config.log_level, Level::ERROR);
+        assert_eq!(
+            config.cors_origins,
+            ["https://a.example", "https://b.example"]
+        );
+    }
+
+    #[test]
+    fn prod_requires_secure_sessions() {
+        let prod = [
+            ("APP_PROFILE", "prod"),
+            ("CORS_ORIGINS", "https://blaze.example"),
+        ];
+        let config = load(&prod, "").unwrap();
+        assert!(config.session_secure);
+        assert_eq!(config.log_level, Level::INFO);
+        assert_eq!(config.shutdown_readiness_delay_secs, 5);
+
+        let result = load(&prod, "session_secure = false");
+        assert_eq!(invalid_key(result), "SESSION_SECURE");
+    }
+
+    #[test]
+    fn length_limits_are_read_and_bounded_by_their_column() {
+        let config = load(&[("TITLE_MAX_LENGTH", "40")], "content_min_length = 10").unwrap();
+        assert_eq!(config.limits.title.max, 40);
+        assert_eq!(config.limits.content.min, 10);
+
+        let result = load(&[("TITLE_MAX_LENGTH", "51")], "");
+        assert_eq!(invalid_key(result), "TITLE_MAX_LENGTH");
+        let result = load(&[], "username_max_length = 101");
+        assert_eq!(invalid_key(result), "USERNAME_MAX_LENGTH");
+        let result = load(&[("PASSWORD_MIN_LENGTH", "50")], "");
+        assert_eq!(invalid_key(result), "PASSWORD_MAX_LENGTH");
+    }
 }
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tower_sessions_redis_store::fred::{clients::SubscriberClient, prelude::*};
+use uuid::Uuid;
+
+const CHANNEL_PREFIX: &str = "blaze:";
+const LOCAL_BUFFER: usize = 1024;
+
+pub enum Channel {
+    User(Uuid),
+    Post(Uuid),
+}
+
+impl Channel {
+    pub fn name(&self) -> String {
+        match self {
+            Channel::User(id) => format!("{}user:{}", CHANNEL_PREFIX, id),
+            Channel::Post(id) => format!("{}post:{}", CHANNEL_PREFIX, id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    Notification {
+        kind: String,
+        actor_id: Uuid,
+        post_id: Option<Uuid>,
+        comment_id: Option<Uuid>,
+    },
+    Comment {
+        post_id: Uuid,
+        comment_id: Uuid,
+        user_id: Uuid,
+        username: String,
+        content: String,
+    },
+    Reactions {
+        post_id: Uuid,
+        like_count: i64,
+        dislike_count: i64,
+    },
+    Message {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        username: String,
+        content: String,
+    },
+    /// A block or mute involving the user changed. Streams reload what they
+    /// hide instead of passing it on, so nobody learns who blocked them.
+    Relationships,
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Notification { .. } => "notification",
+            Event::Comment { .. } => "comment",
+            Event::Reactions { .. } => "reactions",
+            Event::Message { .. } => "message",
+            Event::Relationships => "relationships",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelMessage {
+    pub channel: String,
+    pub event: Event,
+}
+
+/// Fans events out to every connected stream. When Redis is configured,
+/// events are published to Redis and delivered back to every server instance
+/// through a pattern subscription; otherwise they stay in process.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChannelMessage>,
+    redis: Option<RedisPool>,
+}
+
+impl EventBus {
+    pub fn new(redis: Option<RedisPool>) -> Self {
+        let (sender, _) = broadcast::channel(LOCAL_BUFFER);
+        EventBus { sender, redis }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChannelMessage> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an event. Delivery is best effort, so failures are logged
+    /// rather than returned to the request that triggered the event.
+    pub async fn publish(&self, channel: Channel, event: Event) {
+        let channel = channel.name();
+        match &self.redis {
+            Some(redis) => {
+                let payload = match serde_json::to_string(&event) {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        tracing::warn!("failed to serialize event: {:?}", err);
+                        return;
+                    }
+                };
+                if let Err(err) = redis.next().publish::<(), _, _>(channel, payload).await {
+                    tracing::warn!("failed to publish event: {:?}", err);
+                }
+            }
+            None => {
+                // Nobody listening is not an error.
+                let _ = self.sender.send(ChannelMessage { channel, event });
+            }
+        }
+    }
+
+    /// Subscribes to every event channel on Redis and forwards the messages
+    /// to the local streams.
+    pub async fn listen(&self, config: RedisConfig) -> Result<(), RedisError> {
+        let subscriber =
+            SubscriberClient::new(config, None, None, Some(ReconnectPolicy::default()));
+        subscriber.init().await?;
+        subscriber.manage_subscriptions();
+        subscriber
+            .psubscribe(format!("{}*", CHANNEL_PREFIX))
+            .await?;
+
+        let mut messages = subscriber.message_rx();
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            // Keep the subscriber connection alive for as long as we listen.
+            let _subscriber = subscriber;
+            loop {
+                let message = match messages.recv().await {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("event listener skipped {} messages", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(payload) = message.value.as_str() else {
+                    continue;
+                };
+                match serde_json::from_str::<Event>(&payload) {
+                    Ok(event) => {
+                        let _ = sender.send(ChannelMessage {
+                            channel: message.channel.to_string(),
+                            event,
+                        });
+                    }
+                    Err(err) => tracing::warn!("received malformed event: {:?}", err),
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::model::{CommentModel, PostModel, ProfileModel, ReactionModel, UserResponse};
+use serde_json::{json, Value};
+use sqlx::{Pool, Postgres};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+pub const EXPORTS_DIR: &str = "./exports";
+pub const EXPORT_RETENTION_DAYS: i32 = 7;
+
+/// Builds the export archive for a user and records the outcome on the
+/// `data_exports` row. Meant to run in the background.
+pub async fn run_export(db: Pool<Postgres>, export_id: Uuid, user_id: Uuid) {
+    let result = write_export(&db, export_id, user_id).await;
+    let (status, file_name) = match result {
+        Ok(file_name) => ("complete", Some(file_name)),
+        Err(err) => {
+            tracing::error!("data export {} failed: {}", export_id, err);
+            ("failed", None)
+        }
+    };
+
+    if let Err(err) = sqlx::query!(
+        "UPDATE data_exports SET status = $1, file_name = $2, completed_at = NOW() WHERE id = $3",
+        status,
+        file_name,
+        export_id
+    )
+    .execute(&db)
+    .await
+    {
+        tracing::error!("failed to record data export {}: {:?}", export_id, err);
+    }
+}
+
+async fn write_export(
+    db: &Pool<Postgres>,
+    export_id: Uuid,
+    user_id: Uuid,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let archive = build_export(db, user_id).await?;
+    let file_name = format!("{}.json", export_id);
+
+    tokio::fs::create_dir_all(EXPORTS_DIR).await?;
+    tokio::fs::write(
+        PathBuf::from(EXPORTS_DIR).join(&file_name),
+        serde_json::to_vec_pretty(&archive)?,
+    )
+    .await?;
+    Ok(file_name)
+}
+
+async fn build_export(db: &Pool<Postgres>, user_id: Uuid) -> Result<Value, sqlx::Error> {
+    let user = sqlx::query_as!(
+        UserResponse,
+        "SELECT id, username, email, created_at, updated_at FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    let profile = sqlx::query_as!(
+        ProfileModel,
+        "SELECT id, user_id, profile_image, bio, created_at, updated_at FROM profiles WHERE user_id = $1",
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    let posts = sqlx::query_as!(
+        PostModel,
+        "SELECT id, user_id, title, content, created_at, updated_at FROM posts WHERE user_id = $1 ORDER BY created_at",
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let comments = sqlx::query_as!(
+        CommentModel,
+        "SELECT id, user_id, post_id, content, created_at, updated_at FROM comments WHERE user_id = $1 ORDER BY created_at",
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let reactions = sqlx::query_as!(
+        ReactionModel,
+        "SELECT id, user_id, post_id, reaction_type, created_at, updated_at FROM reactions WHERE user_id = $1 ORDER BY created_at",
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(json!({
+        "exported_at": chrono::Utc::now(),
+        "user": user,
+        "profile": profile,
+        "posts": posts,
+        "comments": comments,
+        "reactions": reactions,
+    }))
+}
+
+/// Deletes exports older than the retention period together with their
+/// files. Returns the number of removed exports.
+pub async fn purge_expired_exports(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let file_names: Vec<Option<String>> = sqlx::query_scalar!(
+        "DELETE FROM data_exports WHERE created_at < NOW() - make_interval(days => $1) RETURNING file_name",
+        EXPORT_RETENTION_DAYS
+    )
+    .fetch_all(db)
+    .await?;
+
+    for file_name in file_names.iter().flatten() {
+        remove_export_file(file_name).await;
+    }
+    Ok(file_names.len() as u64)
+}
+
+pub async fn remove_export_file(file_name: &str) {
+    let path = PathBuf::from(EXPORTS_DIR).join(file_name);
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => tracing::warn!("failed to remove {}: {:?}", path.display(), err),
+    }
+}
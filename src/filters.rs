@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[allow(non_snake_case)]
+#[allow(non_snake_case, dead_code)]
 #[derive(Deserialize, Serialize, Clone)]
 pub struct FilterdUser {
     pub id: uuid::Uuid,
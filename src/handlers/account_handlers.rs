@@ -0,0 +1,199 @@
+use crate::{
+    exports::{self, EXPORTS_DIR},
+    model::{DataExportResponse, UserModel},
+    response::{AppError, AppJson, AppPath, JsendResponse},
+    schema::DeleteAccountSchema,
+    AppState,
+};
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use axum::{
+    extract::State,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::json;
+use std::{path::PathBuf, sync::Arc};
+use tower_sessions::Session;
+use uuid::Uuid;
+use validator::ValidateArgs;
+
+#[utoipa::path(
+    delete,
+    path = "/me",
+    tag = "account",
+    request_body = DeleteAccountSchema,
+    responses(
+        (status = 200, description = "Account scheduled for deletion", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Password is incorrect", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn delete_account(
+    session: Session,
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppJson(body): AppJson<DeleteAccountSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate_with_args(&data.env.limits)?;
+
+    let is_valid = match PasswordHash::new(&user.password) {
+        Ok(parsed_hash) => Argon2::default()
+            .verify_password(body.password.as_bytes(), &parsed_hash)
+            .is_ok(),
+        Err(_) => false,
+    };
+
+    if !is_valid {
+        return Err(AppError::Forbidden(
+            json!({"password" : "password is incorrect"}),
+        ));
+    }
+
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    let delete_after = data
+        .repos
+        .users
+        .schedule_deletion(user_id, data.env.account_deletion_grace_days)
+        .await?;
+
+    session.delete().await?;
+
+    let response = JsendResponse {
+        message: Some("account scheduled for deletion, log in again to cancel".to_string()),
+        ..JsendResponse::success(Some(json!({
+            "delete_after": delete_after
+        })))
+    };
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/me/export",
+    tag = "account",
+    responses(
+        (status = 200, description = "Export started", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 409, description = "An export is already in progress", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn create_export(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+
+    let pending: bool = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM data_exports WHERE user_id = $1 AND status = 'pending')",
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await?
+    .unwrap_or(false);
+
+    if pending {
+        return Err(AppError::Conflict(
+            json!({"export" : "an export is already in progress"}),
+        ));
+    }
+
+    let export_id: Uuid = sqlx::query_scalar!(
+        "INSERT INTO data_exports (user_id) VALUES ($1) RETURNING id",
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    tokio::spawn(exports::run_export(data.db.clone(), export_id, user_id));
+
+    let response = JsendResponse::success(Some(json!({
+        "export_id": export_id,
+        "status": "pending",
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/export/{export_id}",
+    tag = "account",
+    params(("export_id" = Uuid, Path, description = "Export ID")),
+    responses(
+        (status = 200, description = "Export status", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Export does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_export(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(exportid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let export_id = parse_export_id(&exportid)?;
+    let export = sqlx::query_as!(
+        DataExportResponse,
+        "SELECT id, status, created_at, completed_at FROM data_exports WHERE id = $1 AND user_id = $2",
+        export_id,
+        user.id
+    )
+    .fetch_optional(&data.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(json!({"export" : "export does not exist"})))?;
+
+    let response = JsendResponse::success(Some(json!({ "export": export })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/export/{export_id}/download",
+    tag = "account",
+    params(("export_id" = Uuid, Path, description = "Export ID")),
+    responses(
+        (status = 200, description = "Exported data as a JSON file", content_type = "application/json"),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 409, description = "Export is not ready", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn download_export(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(exportid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let export_id = parse_export_id(&exportid)?;
+    let file_name = sqlx::query_scalar!(
+        "SELECT file_name FROM data_exports WHERE id = $1 AND user_id = $2 AND status = 'complete'",
+        export_id,
+        user.id
+    )
+    .fetch_optional(&data.db)
+    .await?
+    .flatten()
+    .ok_or_else(|| AppError::Conflict(json!({"export" : "export is not ready"})))?;
+
+    let contents = tokio::fs::read(PathBuf::from(EXPORTS_DIR).join(&file_name)).await?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/json".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"blaze-export-{}\"", file_name),
+            ),
+        ],
+        contents,
+    ))
+}
+
+fn parse_export_id(exportid: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(exportid)
+        .map_err(|_| AppError::BadRequest(json!({"export_id" : "not a valid UUID"})))
+}
@@ -1,146 +1,164 @@
 use crate::{
+    accounts, metrics,
     model::UserModel,
+    repo::{CreateUserError, NewUser},
     response::{AppError, AppJson, JsendResponse},
     schema::{LoginUserSchema, RegisterUserSchema},
     AppState,
 };
 
-use argon2::{
-    password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
-};
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
 
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
 use tower_sessions::Session;
-use uuid::Uuid;
-use validator::Validate;
-
+use validator::ValidateArgs;
+
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginUserSchema,
+    responses(
+        (status = 200, description = "Logged in", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Unknown user or incorrect password", body = JsendResponse),
+        (status = 403, description = "Account is banned", body = JsendResponse),
+    ),
+)]
 pub async fn login_handler(
     session: Session,
     State(data): State<Arc<AppState>>,
     AppJson(body): AppJson<LoginUserSchema>,
 ) -> Result<impl IntoResponse, AppError> {
-    body.validate()?;
-    let user: UserModel = sqlx::query_as!(
-        UserModel,
-        "SELECT * FROM users WHERE username = $1",
-        body.username
-    )
-    .fetch_optional(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?
-    .ok_or_else(|| AppError::JsendFail(json!({"username" : "user does not exist"})))?;
+    body.validate_with_args(&data.env.limits)?;
+    let user: UserModel = match data.repos.users.find_by_username(&body.username).await? {
+        Some(user) => user,
+        None => {
+            metrics::record_login(false);
+            return Err(AppError::Unauthorized(
+                json!({"username" : "user does not exist"}),
+            ));
+        }
+    };
 
     let is_valid = match PasswordHash::new(&user.password) {
         Ok(parsed_hash) => Argon2::default()
             .verify_password(body.password.as_bytes(), &parsed_hash)
-            .map_or(false, |_| true),
+            .is_ok(),
         Err(_) => false,
     };
 
     if !is_valid {
-        return Err(AppError::JsendFail(
+        metrics::record_login(false);
+        return Err(AppError::Unauthorized(
             json!({"password" : "password is incorrect"}),
         ));
     }
 
-    session
-        .insert("user_id", user.id)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+    if user.banned_at.is_some() {
+        metrics::record_login(false);
+        return Err(AppError::Forbidden(
+            json!({"account" : "account is banned"}),
+        ));
+    }
+
+    // Logging in during the deletion grace period cancels the deletion.
+    let deletion_cancelled = user.delete_after.is_some();
+    if deletion_cancelled {
+        let user_id = user.id.ok_or(AppError::InternalServerError)?;
+        data.repos.users.cancel_deletion(user_id).await?;
+    }
+
+    session.insert("user_id", user.id).await?;
+    metrics::record_login(true);
 
     let response = JsendResponse::success(Some(json!({
-        "username" : body.username
+        "username" : body.username,
+        "deletion_cancelled" : deletion_cancelled,
     })));
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Logged out", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn logout_handler(session: Session) -> Result<impl IntoResponse, AppError> {
-    session
-        .delete()
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+    session.delete().await?;
     let response: JsendResponse = JsendResponse::success(None);
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body = RegisterUserSchema,
+    responses(
+        (status = 200, description = "Registered", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 409, description = "Username or email already exists", body = JsendResponse),
+    ),
+)]
 pub async fn register_handler(
     State(data): State<Arc<AppState>>,
     AppJson(body): AppJson<RegisterUserSchema>,
 ) -> Result<impl IntoResponse, AppError> {
-    body.validate()?;
-    let user_exists: bool = sqlx::query_scalar!(
-        "SELECT EXISTS (SELECT 1 FROM users WHERE username = $1)",
-        body.username.to_owned()
-    )
-    .fetch_one(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?
-    .unwrap_or(false);
-
-    let email_exists: bool = sqlx::query_scalar!(
-        "SELECT EXISTS (SELECT 1 FROM users WHERE email = $1)",
-        body.email
-    )
-    .fetch_one(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?
-    .unwrap_or(false);
-
-    let mut fails: HashMap<String, String> = HashMap::new();
-    if user_exists {
-        fails.insert(
-            "username".to_string(),
-            "username already exists".to_string(),
-        );
-    }
-    if email_exists {
-        fails.insert("email".to_string(), "email already exists".to_string());
+    body.validate_with_args(&data.env.limits)?;
+
+    let hashed_password =
+        accounts::hash_password(&body.password).map_err(|_| AppError::InternalServerError)?;
+
+    let created = data
+        .repos
+        .users
+        .create(NewUser {
+            username: &body.username,
+            email: &body.email,
+            password_hash: &hashed_password,
+        })
+        .await;
+
+    match created {
+        Ok(_) => {}
+        Err(CreateUserError::Taken { username, email }) => {
+            let mut fails: HashMap<String, String> = HashMap::new();
+            if username {
+                fails.insert(
+                    "username".to_string(),
+                    "username already exists".to_string(),
+                );
+            }
+            if email {
+                fails.insert("email".to_string(), "email already exists".to_string());
+            }
+            return Err(AppError::Conflict(json!(fails)));
+        }
+        Err(CreateUserError::Database(err)) => return Err(err.into()),
     }
-    if !fails.is_empty() {
-        return Err(AppError::JsendFail(json!(fails)));
-    }
-
-    let salt = SaltString::generate(&mut OsRng);
-    let hashed_password = Argon2::default()
-        .hash_password(body.password.as_bytes(), &salt)
-        .map_err(|_| AppError::InternalServerError)
-        .map(|hash| hash.to_string())?;
-
-    let tx = data
-        .db
-        .begin()
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
-
-    let user_id: Uuid = sqlx::query_scalar!(
-        "INSERT INTO users (username, email, password) VALUES ($1, $2, $3) RETURNING id",
-        body.username.to_string(),
-        body.email.to_string().to_ascii_lowercase(),
-        hashed_password
-    )
-    .fetch_one(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
-
-    sqlx::query!("INSERT INTO profiles (user_id, profile_image, bio) VALUES ($1, $2, $3) RETURNING id, user_id,profile_image,bio,created_at,updated_at",
-        user_id,
-        "default.jpg".to_string(),
-        "".to_string(),
-    )
-    .fetch_one(&data.db)
-    .await.map_err(|_| { AppError::InternalServerError})?;
-
-    tx.commit()
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
 
     let response: JsendResponse = JsendResponse::success(None);
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/status",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Current user", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn status_handler(
     Extension(user): Extension<UserModel>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -0,0 +1,277 @@
+use crate::{
+    events::{Channel, Event},
+    model::{RelationshipResponse, UserModel},
+    response::{AppError, AppPath, AppQuery, JsendResponse},
+    schema::PaginationSchema,
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    post,
+    path = "/user/{username}/block",
+    tag = "relationships",
+    params(("username" = String, Path, description = "Username")),
+    responses(
+        (status = 200, description = "User blocked", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "User does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn block_user(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(username): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    let target_id = find_other_user(&data, &user, &username).await?;
+    sqlx::query!(
+        "INSERT INTO blocks (blocker_id, blocked_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        user_id,
+        target_id
+    )
+    .execute(&data.db)
+    .await?;
+    relationships_changed(&data, &[user_id, target_id]).await;
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/user/{username}/block",
+    tag = "relationships",
+    params(("username" = String, Path, description = "Username")),
+    responses(
+        (status = 200, description = "User unblocked", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "User or block does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn unblock_user(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(username): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    let target_id = find_other_user(&data, &user, &username).await?;
+    let deleted = sqlx::query!(
+        "DELETE FROM blocks WHERE blocker_id = $1 AND blocked_id = $2",
+        user_id,
+        target_id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(
+            json!({"username" : "user is not blocked"}),
+        ));
+    }
+    relationships_changed(&data, &[user_id, target_id]).await;
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/{username}/mute",
+    tag = "relationships",
+    params(("username" = String, Path, description = "Username")),
+    responses(
+        (status = 200, description = "User muted", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "User does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn mute_user(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(username): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    let target_id = find_other_user(&data, &user, &username).await?;
+    sqlx::query!(
+        "INSERT INTO mutes (muter_id, muted_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        user_id,
+        target_id
+    )
+    .execute(&data.db)
+    .await?;
+    relationships_changed(&data, &[user_id]).await;
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/user/{username}/mute",
+    tag = "relationships",
+    params(("username" = String, Path, description = "Username")),
+    responses(
+        (status = 200, description = "User unmuted", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "User or mute does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn unmute_user(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(username): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    let target_id = find_other_user(&data, &user, &username).await?;
+    let deleted = sqlx::query!(
+        "DELETE FROM mutes WHERE muter_id = $1 AND muted_id = $2",
+        user_id,
+        target_id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(
+            json!({"username" : "user is not muted"}),
+        ));
+    }
+    relationships_changed(&data, &[user_id]).await;
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/blocks",
+    tag = "relationships",
+    params(PaginationSchema),
+    responses(
+        (status = 200, description = "Blocked users", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_blocks(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let users: Vec<RelationshipResponse> = sqlx::query_as!(
+        RelationshipResponse,
+        "SELECT
+            users.id AS user_id,
+            users.username,
+            profiles.profile_image,
+            blocks.created_at
+        FROM blocks
+        JOIN users ON blocks.blocked_id = users.id
+        JOIN profiles ON profiles.user_id = users.id
+        WHERE blocks.blocker_id = $1
+        ORDER BY blocks.created_at DESC
+        LIMIT $2 OFFSET $3",
+        user.id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "users": users,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/mutes",
+    tag = "relationships",
+    params(PaginationSchema),
+    responses(
+        (status = 200, description = "Muted users", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_mutes(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let users: Vec<RelationshipResponse> = sqlx::query_as!(
+        RelationshipResponse,
+        "SELECT
+            users.id AS user_id,
+            users.username,
+            profiles.profile_image,
+            mutes.created_at
+        FROM mutes
+        JOIN users ON mutes.muted_id = users.id
+        JOIN profiles ON profiles.user_id = users.id
+        WHERE mutes.muter_id = $1
+        ORDER BY mutes.created_at DESC
+        LIMIT $2 OFFSET $3",
+        user.id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "users": users,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+async fn find_other_user(
+    data: &AppState,
+    user: &UserModel,
+    username: &str,
+) -> Result<Uuid, AppError> {
+    let target_id = sqlx::query_scalar!("SELECT id FROM users WHERE username = $1", username)
+        .fetch_optional(&data.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(json!({"username" : "user does not exist"})))?;
+
+    if user.id == Some(target_id) {
+        return Err(AppError::BadRequest(
+            json!({"username" : "cannot target yourself"}),
+        ));
+    }
+    Ok(target_id)
+}
+
+/// Tells the open event streams of the users involved to reload who they hide.
+async fn relationships_changed(data: &AppState, user_ids: &[Uuid]) {
+    for user_id in user_ids {
+        data.events
+            .publish(Channel::User(*user_id), Event::Relationships)
+            .await;
+    }
+}
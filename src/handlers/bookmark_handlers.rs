@@ -0,0 +1,169 @@
+use crate::{
+    model::{Mentions, PostResponse, UserModel},
+    response::{AppError, AppJson, AppPath, AppQuery, JsendResponse},
+    schema::{BookmarkFilterSchema, BookmarkSchema, PaginationSchema},
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::{Validate, ValidateArgs};
+
+#[utoipa::path(
+    post,
+    path = "/posts/{post_id}/bookmark",
+    tag = "bookmarks",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    request_body = BookmarkSchema,
+    responses(
+        (status = 200, description = "Post bookmarked", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn create_bookmark(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(postid): AppPath<String>,
+    AppJson(body): AppJson<BookmarkSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate_with_args(&data.env.limits)?;
+    let post_id = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+
+    let post_exists: bool = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM posts WHERE id = $1 AND deleted_at IS NULL)",
+        post_id
+    )
+    .fetch_one(&data.db)
+    .await?
+    .unwrap_or(false);
+
+    if !post_exists {
+        return Err(AppError::NotFound(json!({"post" : "post doesnt exist"})));
+    }
+
+    sqlx::query!(
+        "INSERT INTO bookmarks (user_id, post_id, collection) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, post_id)
+        DO UPDATE SET collection = EXCLUDED.collection, updated_at = NOW()",
+        user.id,
+        post_id,
+        body.collection
+    )
+    .execute(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "post_id": post_id,
+        "collection": body.collection,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/posts/{post_id}/bookmark",
+    tag = "bookmarks",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    responses(
+        (status = 200, description = "Bookmark removed", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Bookmark does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn delete_bookmark(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(postid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let post_id = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM bookmarks WHERE user_id = $1 AND post_id = $2",
+        user.id,
+        post_id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(
+            json!({"bookmark" : "bookmark does not exist"}),
+        ));
+    }
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/bookmarks",
+    tag = "bookmarks",
+    params(
+        PaginationSchema,
+        BookmarkFilterSchema,
+    ),
+    responses(
+        (status = 200, description = "Bookmarked posts", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_bookmarks(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+    AppQuery(filter): AppQuery<BookmarkFilterSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let posts: Vec<PostResponse> = sqlx::query_as!(
+        PostResponse,
+        r#"SELECT
+            posts.id,
+            users.username,
+            posts.title,
+            posts.content,
+            posts.created_at,
+            posts.updated_at,
+            posts.user_id,
+            profiles.profile_image,
+            posts.like_count AS likes,
+            posts.dislike_count AS dislikes,
+            posts.comment_count AS comments,
+            post_mentions(posts.id) AS "mentions!: Mentions"
+        FROM bookmarks
+        JOIN posts ON bookmarks.post_id = posts.id
+        JOIN users ON posts.user_id = users.id
+        JOIN profiles ON profiles.user_id = users.id
+        WHERE bookmarks.user_id = $1
+            AND ($2::VARCHAR IS NULL OR bookmarks.collection = $2)
+            AND posts.deleted_at IS NULL
+            AND NOT is_hidden_from(posts.user_id, $1)
+        ORDER BY bookmarks.created_at DESC
+        LIMIT $3 OFFSET $4"#,
+        user.id,
+        filter.collection,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "posts": posts,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
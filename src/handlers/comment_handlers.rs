@@ -1,68 +1,111 @@
 use crate::{
-    model::{CommentResponse,UserModel},
+    events::{Channel, Event},
+    model::UserModel,
+    notifications,
     response::{AppError, AppJson, AppPath, JsendResponse},
-    schema::CommentSchema, 
+    schema::CommentSchema,
     AppState,
 };
-use axum::{
-    extract::State,
-    response::IntoResponse,
-    Extension, Json,
-};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
 use serde_json::json;
-use validator::Validate;
 use std::sync::Arc;
 use uuid::Uuid;
+use validator::ValidateArgs;
 
+#[utoipa::path(
+    get,
+    path = "/posts/{post_id}/comments",
+    tag = "comments",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    responses(
+        (status = 200, description = "Comments on the post", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+    ),
+)]
 pub async fn get_comments_handler(
+    viewer: Option<Extension<UserModel>>,
     AppPath(postid): AppPath<String>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, AppError> {
-    let postid = Uuid::parse_str(&postid).map_err(|_| AppError::JsendFail(json!({"post_id" : "not a valid UUID"})))?;
-    let comments = sqlx::query_as!(
-        CommentResponse,
-        "SELECT
-            comments.id,
-            users.username,
-            comments.user_id,
-            comments.post_id,
-            comments.content,
-            comments.created_at,
-            comments.updated_at,
-            profiles.profile_image
-        FROM comments
-        JOIN users ON comments.user_id = users.id
-        JOIN profiles ON comments.user_id = profiles.user_id
-        WHERE comments.post_id = $1
-        ORDER BY comments.created_at DESC",
-        postid
-    )
-    .fetch_all(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
-    let response =  JsendResponse::success(Some(json!({
+    let viewer_id = viewer.and_then(|Extension(viewer)| viewer.id);
+    let postid = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+    let comments = data.repos.comments.list(postid, viewer_id).await?;
+    let response = JsendResponse::success(Some(json!({
         "comments" : Some(comments)
     })));
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/posts/{post_id}/comments",
+    tag = "comments",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    request_body = CommentSchema,
+    responses(
+        (status = 200, description = "Comment created", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Post author is blocked", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn create_comment_handler(
     State(data): State<Arc<AppState>>,
     Extension(user): Extension<UserModel>,
     AppPath(postid): AppPath<String>,
     AppJson(comment): AppJson<CommentSchema>,
 ) -> Result<impl IntoResponse, AppError> {
-    comment.validate()?;
-    let postid = Uuid::parse_str(&postid).map_err(|_| AppError::JsendFail(json!({"post_id" : "not a valid UUID"})))?;
-    sqlx::query!(
-        "INSERT INTO comments (content,user_id,post_id) VALUES ($1,$2,$3)",
-        comment.content,
-        user.id,
-        postid
-    )
-    .execute(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
-    let response = JsendResponse::success(None);
+    comment.validate_with_args(&data.env.limits)?;
+    let postid = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+
+    let author_id = data
+        .repos
+        .posts
+        .author(postid)
+        .await?
+        .ok_or(AppError::NotFound(json!({"post" : "post doesnt exist"})))?;
+
+    let blocked = data
+        .repos
+        .users
+        .is_blocked_between(user_id, author_id)
+        .await?;
+    if blocked {
+        return Err(AppError::Forbidden(
+            json!({"post" : "cannot comment on posts from a blocked user"}),
+        ));
+    }
+
+    let (comment_id, stored) = data
+        .repos
+        .comments
+        .create(postid, author_id, user_id, &comment.content)
+        .await?;
+
+    data.events
+        .publish(
+            Channel::Post(postid),
+            Event::Comment {
+                post_id: postid,
+                comment_id,
+                user_id,
+                username: user.username,
+                content: comment.content,
+            },
+        )
+        .await;
+    for notification in &stored {
+        notifications::publish(&data.events, notification).await;
+    }
+
+    let response = JsendResponse::success(Some(json!({
+        "comment_id": comment_id
+    })));
     Ok(Json(response))
-}
\ No newline at end of file
+}
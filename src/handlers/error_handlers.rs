@@ -1,8 +1,6 @@
-use crate::response::JsendResponse;
-use axum::response::IntoResponse;
-use axum::response::Json;
+use crate::response::AppError;
+use serde_json::json;
 
-pub async fn fallback_handler() -> impl IntoResponse {
-    let response = JsendResponse::error("incorrect path placeholder message".to_string());
-    Json(response)
+pub async fn fallback_handler() -> AppError {
+    AppError::NotFound(json!({"path" : "the requested resource was not found"}))
 }
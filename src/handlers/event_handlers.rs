@@ -0,0 +1,144 @@
+use crate::{
+    events::{Channel, Event},
+    model::UserModel,
+    response::{AppError, AppQuery, JsendResponse},
+    schema::EventStreamSchema,
+    shutdown, AppState,
+};
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Extension,
+};
+use serde_json::json;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use uuid::Uuid;
+
+const MAX_WATCHED_POSTS: usize = 50;
+
+/// Streams the user's notifications, plus new comments and reaction counts
+/// for the posts listed in `?posts=<id>,<id>`, as Server-Sent Events.
+/// Posts that are deleted or hidden from the viewer are not watched, and
+/// comments by users the viewer blocked, muted or was blocked by are left out
+/// as they are over REST. A `resync` event means the stream fell behind and
+/// dropped events, so the client should refetch. The stream ends when the
+/// server starts shutting down.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(EventStreamSchema),
+    responses(
+        (status = 200, description = "Server-sent event stream", content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn stream_events(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(query): AppQuery<EventStreamSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+
+    let post_ids = query
+        .posts
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(Uuid::parse_str)
+        .collect::<Result<Vec<Uuid>, _>>()
+        .map_err(|_| AppError::BadRequest(json!({"posts" : "not a list of valid UUIDs"})))?;
+
+    if post_ids.len() > MAX_WATCHED_POSTS {
+        return Err(AppError::BadRequest(
+            json!({"posts" : format!("cannot watch more than {} posts", MAX_WATCHED_POSTS)}),
+        ));
+    }
+
+    let hidden = data.repos.users.hidden_from(user_id).await?;
+    // Each channel with the user it belongs to: the author for a post, so
+    // only posts visible to the viewer are watched.
+    let mut channels: HashMap<String, Uuid> = HashMap::new();
+    for post_id in post_ids {
+        match data.repos.posts.author(post_id).await? {
+            Some(author_id) if !hidden.contains(&author_id) => {
+                channels.insert(Channel::Post(post_id).name(), author_id);
+            }
+            _ => {}
+        }
+    }
+    channels.insert(Channel::User(user_id).name(), user_id);
+
+    let hidden = Arc::new(Mutex::new(hidden));
+    let channels = Arc::new(channels);
+    let shutdown_requested = shutdown::requested(data.shutdown.clone());
+
+    let stream = futures_util::StreamExt::filter_map(
+        BroadcastStream::new(data.events.subscribe()),
+        move |message| {
+            let data = data.clone();
+            let channels = channels.clone();
+            let hidden = hidden.clone();
+            async move {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                        // A block or mute may have been among the skipped events.
+                        refresh_hidden(&data, user_id, &hidden).await;
+                        let event = SseEvent::default()
+                            .event("resync")
+                            .json_data(json!({"skipped": skipped}))
+                            .ok()?;
+                        return Some(Ok::<_, Infallible>(event));
+                    }
+                };
+                // A post stops streaming once its author is hidden.
+                let owner_id = channels.get(&message.channel)?;
+                if hidden.lock().unwrap().contains(owner_id) {
+                    return None;
+                }
+                match &message.event {
+                    Event::Relationships => {
+                        refresh_hidden(&data, user_id, &hidden).await;
+                        return None;
+                    }
+                    Event::Comment {
+                        user_id: author_id, ..
+                    } if hidden.lock().unwrap().contains(author_id) => {
+                        return None;
+                    }
+                    _ => {}
+                }
+                let event = SseEvent::default()
+                    .event(message.event.name())
+                    .json_data(&message.event)
+                    .ok()?;
+                Some(Ok(event))
+            }
+        },
+    );
+    // The stream would otherwise never end, holding up graceful shutdown
+    // until its deadline.
+    let stream = futures_util::StreamExt::take_until(stream, shutdown_requested);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Reloads the users whose comments the stream hides, keeping the previous
+/// set when the database cannot be reached.
+async fn refresh_hidden(data: &AppState, user_id: Uuid, hidden: &Mutex<HashSet<Uuid>>) {
+    match data.repos.users.hidden_from(user_id).await {
+        Ok(users) => *hidden.lock().unwrap() = users,
+        Err(err) => tracing::warn!("failed to reload hidden users: {:?}", err),
+    }
+}
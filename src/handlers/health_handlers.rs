@@ -0,0 +1,44 @@
+use crate::{health, response::JsendResponse, AppState};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Liveness: answers as long as the process is serving requests.
+pub async fn healthz() -> impl IntoResponse {
+    Json(JsendResponse::success(None))
+}
+
+/// Readiness: checks every dependency and fails while shutting down.
+pub async fn readyz(State(data): State<Arc<AppState>>) -> impl IntoResponse {
+    if data.readiness.is_shutting_down() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(JsendResponse::fail(json!({"server" : "shutting down"}))),
+        );
+    }
+
+    let (postgres, redis) = tokio::join!(health::check_postgres(&data.db), async {
+        match &data.redis {
+            Some(redis) => Some(health::check_redis(redis).await),
+            None => None,
+        }
+    });
+
+    let ready = postgres.healthy && redis.as_ref().is_none_or(|redis| redis.healthy);
+    let mut dependencies = json!({ "postgres": postgres });
+    if let Some(redis) = redis {
+        dependencies["redis"] = json!(redis);
+    }
+
+    if ready {
+        (
+            StatusCode::OK,
+            Json(JsendResponse::success(Some(dependencies))),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(JsendResponse::fail(dependencies)),
+        )
+    }
+}
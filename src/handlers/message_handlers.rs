@@ -0,0 +1,425 @@
+use crate::{
+    blocks,
+    events::{Channel, Event},
+    model::{ConversationMembers, ConversationResponse, MessageResponse, UserModel},
+    response::{AppError, AppJson, AppPath, AppQuery, JsendResponse},
+    schema::{CreateConversationSchema, MessageSchema, PaginationSchema},
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::{Validate, ValidateArgs};
+
+#[utoipa::path(
+    post,
+    path = "/conversations",
+    tag = "messages",
+    request_body = CreateConversationSchema,
+    responses(
+        (status = 200, description = "Conversation created or reused", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "A member is blocked", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn create_conversation(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppJson(body): AppJson<CreateConversationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate_with_args(&data.env.limits)?;
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+
+    let mut usernames: Vec<String> = body
+        .usernames
+        .into_iter()
+        .filter(|username| *username != user.username)
+        .collect();
+    usernames.sort();
+    usernames.dedup();
+    if usernames.is_empty() {
+        return Err(AppError::Validation(
+            json!({"usernames" : "add at least one other user"}),
+        ));
+    }
+
+    let members = sqlx::query!(
+        "SELECT id, username FROM users WHERE username = ANY($1)",
+        &usernames
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    if let Some(missing) = usernames
+        .iter()
+        .find(|username| !members.iter().any(|member| member.username == **username))
+    {
+        return Err(AppError::Validation(
+            json!({"usernames" : format!("user {} does not exist", missing)}),
+        ));
+    }
+
+    for member in &members {
+        let blocked = blocks::is_blocked_between(&data.db, user_id, member.id).await?;
+        if blocked {
+            return Err(AppError::Forbidden(
+                json!({"usernames" : format!("cannot message {}", member.username)}),
+            ));
+        }
+    }
+
+    let mut tx = data.db.begin().await?;
+
+    let conversation_id: Uuid = if let [member] = members.as_slice() {
+        // One-to-one conversations are reused rather than duplicated.
+        let direct_key = if user_id < member.id {
+            format!("{}:{}", user_id, member.id)
+        } else {
+            format!("{}:{}", member.id, user_id)
+        };
+        sqlx::query_scalar!(
+            "INSERT INTO conversations (created_by, is_group, direct_key) VALUES ($1, FALSE, $2)
+            ON CONFLICT (direct_key) DO UPDATE SET direct_key = EXCLUDED.direct_key
+            RETURNING id",
+            user_id,
+            direct_key
+        )
+        .fetch_one(&mut *tx)
+        .await?
+    } else {
+        sqlx::query_scalar!(
+            "INSERT INTO conversations (created_by, is_group, title) VALUES ($1, TRUE, $2) RETURNING id",
+            user_id,
+            body.title
+        )
+        .fetch_one(&mut *tx)
+        .await?
+    };
+
+    let mut member_ids: Vec<Uuid> = members.iter().map(|member| member.id).collect();
+    member_ids.push(user_id);
+    sqlx::query!(
+        "INSERT INTO conversation_members (conversation_id, user_id)
+        SELECT $1, member_id FROM UNNEST($2::UUID[]) AS member_id
+        ON CONFLICT DO NOTHING",
+        conversation_id,
+        &member_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "conversation_id": conversation_id
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/conversations",
+    tag = "messages",
+    params(PaginationSchema),
+    responses(
+        (status = 200, description = "Conversations of the user", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_conversations(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let conversations: Vec<ConversationResponse> = sqlx::query_as!(
+        ConversationResponse,
+        r#"SELECT
+            conversations.id,
+            conversations.is_group,
+            conversations.title,
+            (SELECT json_agg(json_build_object('user_id', users.id, 'username', users.username) ORDER BY users.username)
+                FROM conversation_members AS members
+                JOIN users ON members.user_id = users.id
+                WHERE members.conversation_id = conversations.id) AS "members!: ConversationMembers",
+            (SELECT messages.content FROM messages
+                WHERE messages.conversation_id = conversations.id
+                ORDER BY messages.created_at DESC LIMIT 1) AS last_message,
+            (SELECT COUNT(*) FROM messages
+                WHERE messages.conversation_id = conversations.id
+                    AND messages.user_id <> $1
+                    AND messages.created_at > COALESCE(conversation_members.last_read_at, '-infinity')) AS "unread_count!",
+            conversations.created_at,
+            conversations.updated_at
+        FROM conversation_members
+        JOIN conversations ON conversation_members.conversation_id = conversations.id
+        WHERE conversation_members.user_id = $1
+        ORDER BY conversations.updated_at DESC
+        LIMIT $2 OFFSET $3"#,
+        user.id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "conversations": conversations,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/conversations/unread_count",
+    operation_id = "get_conversation_unread_count",
+    tag = "messages",
+    responses(
+        (status = 200, description = "Number of unread messages", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_unread_count(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let unread_count: i64 = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!"
+        FROM conversation_members
+        JOIN messages ON messages.conversation_id = conversation_members.conversation_id
+        WHERE conversation_members.user_id = $1
+            AND messages.user_id <> $1
+            AND messages.created_at > COALESCE(conversation_members.last_read_at, '-infinity')"#,
+        user.id
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "unread_count": unread_count
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/conversations/{conversation_id}/messages",
+    tag = "messages",
+    params(
+        ("conversation_id" = Uuid, Path, description = "Conversation ID"),
+        PaginationSchema,
+    ),
+    responses(
+        (status = 200, description = "Messages in the conversation", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Conversation does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_messages(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(conversationid): AppPath<String>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let conversation_id = parse_conversation_id(&conversationid)?;
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    ensure_member(&data, conversation_id, user_id).await?;
+
+    let messages: Vec<MessageResponse> = sqlx::query_as!(
+        MessageResponse,
+        "SELECT
+            messages.id,
+            messages.conversation_id,
+            messages.user_id,
+            users.username,
+            messages.content,
+            messages.created_at
+        FROM messages
+        JOIN users ON messages.user_id = users.id
+        WHERE messages.conversation_id = $1
+        ORDER BY messages.created_at DESC
+        LIMIT $2 OFFSET $3",
+        conversation_id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "messages": messages,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/conversations/{conversation_id}/messages",
+    tag = "messages",
+    params(("conversation_id" = Uuid, Path, description = "Conversation ID")),
+    request_body = MessageSchema,
+    responses(
+        (status = 200, description = "Message sent", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "A member is blocked", body = JsendResponse),
+        (status = 404, description = "Conversation does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn send_message(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(conversationid): AppPath<String>,
+    AppJson(message): AppJson<MessageSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    message.validate_with_args(&data.env.limits)?;
+    let conversation_id = parse_conversation_id(&conversationid)?;
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    ensure_member(&data, conversation_id, user_id).await?;
+
+    let blocked = blocks::is_blocked_in_conversation(&data.db, conversation_id, user_id).await?;
+    if blocked {
+        return Err(AppError::Forbidden(
+            json!({"conversation" : "cannot message a blocked user"}),
+        ));
+    }
+
+    let mut tx = data.db.begin().await?;
+
+    let message_id: Uuid = sqlx::query_scalar!(
+        "INSERT INTO messages (conversation_id, user_id, content) VALUES ($1, $2, $3) RETURNING id",
+        conversation_id,
+        user_id,
+        message.content
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE conversations SET updated_at = NOW() WHERE id = $1",
+        conversation_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE conversation_members SET last_read_at = NOW() WHERE conversation_id = $1 AND user_id = $2",
+        conversation_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let member_ids: Vec<Uuid> = sqlx::query_scalar!(
+        "SELECT user_id FROM conversation_members WHERE conversation_id = $1",
+        conversation_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    for member_id in member_ids {
+        data.events
+            .publish(
+                Channel::User(member_id),
+                Event::Message {
+                    conversation_id,
+                    message_id,
+                    user_id,
+                    username: user.username.clone(),
+                    content: message.content.clone(),
+                },
+            )
+            .await;
+    }
+
+    let response = JsendResponse::success(Some(json!({
+        "message_id": message_id
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/conversations/{conversation_id}/read",
+    operation_id = "mark_conversation_read",
+    tag = "messages",
+    params(("conversation_id" = Uuid, Path, description = "Conversation ID")),
+    responses(
+        (status = 200, description = "Conversation marked as read", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Conversation does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn mark_read(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(conversationid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let conversation_id = parse_conversation_id(&conversationid)?;
+
+    let updated = sqlx::query!(
+        "UPDATE conversation_members SET last_read_at = NOW() WHERE conversation_id = $1 AND user_id = $2",
+        conversation_id,
+        user.id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(AppError::NotFound(
+            json!({"conversation" : "conversation does not exist"}),
+        ));
+    }
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+fn parse_conversation_id(conversationid: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(conversationid)
+        .map_err(|_| AppError::BadRequest(json!({"conversation_id" : "not a valid UUID"})))
+}
+
+async fn ensure_member(
+    data: &AppState,
+    conversation_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let is_member: bool = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM conversation_members WHERE conversation_id = $1 AND user_id = $2)",
+        conversation_id,
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await?
+    .unwrap_or(false);
+
+    if !is_member {
+        // Conversations the user is not part of are reported as missing.
+        return Err(AppError::NotFound(
+            json!({"conversation" : "conversation does not exist"}),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use crate::{metrics, AppState};
+use axum::{extract::State, http::header::CONTENT_TYPE, response::IntoResponse};
+use std::sync::Arc;
+
+/// Prometheus scrape endpoint.
+pub async fn metrics_handler(State(data): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(&data),
+    )
+}
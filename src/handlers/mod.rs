@@ -1,6 +1,15 @@
+pub mod account_handlers;
 pub mod auth_handlers;
+pub mod block_handlers;
+pub mod bookmark_handlers;
 pub mod comment_handlers;
 pub mod error_handlers;
+pub mod event_handlers;
+pub mod health_handlers;
+pub mod message_handlers;
+pub mod metrics_handlers;
+pub mod notification_handlers;
 pub mod post_handlers;
 pub mod profile_handlers;
+pub mod tag_handlers;
 pub mod user_handlers;
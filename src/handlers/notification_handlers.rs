@@ -0,0 +1,168 @@
+use crate::{
+    model::{NotificationResponse, UserModel},
+    response::{AppError, AppPath, AppQuery, JsendResponse},
+    schema::{NotificationFilterSchema, PaginationSchema},
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    tag = "notifications",
+    params(
+        PaginationSchema,
+        NotificationFilterSchema,
+    ),
+    responses(
+        (status = 200, description = "Notifications of the user", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_notifications(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+    AppQuery(filter): AppQuery<NotificationFilterSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let notifications: Vec<NotificationResponse> = sqlx::query_as!(
+        NotificationResponse,
+        r#"SELECT
+            notifications.id,
+            notifications.kind,
+            notifications.actor_id,
+            users.username AS actor_username,
+            profiles.profile_image AS actor_profile_image,
+            (SELECT COUNT(*) FROM notification_actors
+                WHERE notification_actors.notification_id = notifications.id) AS "actor_count!",
+            notifications.post_id,
+            notifications.comment_id,
+            notifications.is_read,
+            notifications.created_at,
+            notifications.updated_at
+        FROM notifications
+        JOIN users ON notifications.actor_id = users.id
+        JOIN profiles ON profiles.user_id = users.id
+        WHERE notifications.user_id = $1
+            AND (NOT $2 OR NOT notifications.is_read)
+        ORDER BY notifications.updated_at DESC
+        LIMIT $3 OFFSET $4"#,
+        user.id,
+        filter.unread_only,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "notifications": notifications,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/notifications/unread_count",
+    operation_id = "get_notification_unread_count",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "Number of unread notifications", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn get_unread_count(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let unread_count: i64 = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM notifications WHERE user_id = $1 AND NOT is_read"#,
+        user.id
+    )
+    .fetch_one(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "unread_count": unread_count
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/{notification_id}/read",
+    operation_id = "mark_notification_read",
+    tag = "notifications",
+    params(("notification_id" = Uuid, Path, description = "Notification ID")),
+    responses(
+        (status = 200, description = "Notification marked as read", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 404, description = "Notification does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn mark_read(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(notificationid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let notification_id = Uuid::parse_str(&notificationid)
+        .map_err(|_| AppError::BadRequest(json!({"notification_id" : "not a valid UUID"})))?;
+
+    let updated = sqlx::query!(
+        "UPDATE notifications SET is_read = TRUE WHERE id = $1 AND user_id = $2",
+        notification_id,
+        user.id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(AppError::NotFound(
+            json!({"notification" : "notification does not exist"}),
+        ));
+    }
+
+    let response = JsendResponse::success(None);
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/notifications/read_all",
+    tag = "notifications",
+    responses(
+        (status = 200, description = "All notifications marked as read", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn mark_all_read(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let updated = sqlx::query!(
+        "UPDATE notifications SET is_read = TRUE WHERE user_id = $1 AND NOT is_read",
+        user.id
+    )
+    .execute(&data.db)
+    .await?
+    .rows_affected();
+
+    let response = JsendResponse::success(Some(json!({
+        "marked_read": updated
+    })));
+    Ok(Json(response))
+}
@@ -1,5 +1,7 @@
 use crate::{
-    model::{PostResponse, UserModel},
+    events::{Channel, Event},
+    model::UserModel,
+    notifications, posts,
     response::{AppError, AppJson, AppPath, JsendResponse},
     schema::{CreatePostSchema, LikePostSchema},
     AppState,
@@ -8,80 +10,153 @@ use axum::{extract::State, response::IntoResponse, Extension, Json};
 use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidateArgs};
 
+#[utoipa::path(
+    get,
+    path = "/posts/{post_id}",
+    tag = "posts",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    responses(
+        (status = 200, description = "The post", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+)]
 pub async fn get_post(
+    viewer: Option<Extension<UserModel>>,
     State(data): State<Arc<AppState>>,
     AppPath(postid): AppPath<String>,
 ) -> Result<impl IntoResponse, AppError> {
+    let viewer_id = viewer.and_then(|Extension(viewer)| viewer.id);
     let postid = Uuid::parse_str(&postid)
-        .map_err(|_| AppError::JsendFail(json!({"post_id" : "not a valid UUID"})))?;
-    let post :PostResponse = sqlx::query_as!(
-        PostResponse,
-        "SELECT
-            posts.id,
-            users.username,
-            posts.title,
-            posts.content,
-            posts.created_at,
-            posts.updated_at,
-            posts.user_id,
-            profiles.profile_image,
-            COALESCE(SUM(CASE WHEN reactions.reaction_type = TRUE THEN 1 ELSE 0 END), 0) AS likes,
-            COALESCE(SUM(CASE WHEN reactions.reaction_type = FALSE THEN 1 ELSE 0 END), 0) AS dislikes
-        FROM posts
-        JOIN users ON posts.user_id = users.id
-        JOIN profiles ON profiles.user_id = users.id
-        LEFT JOIN reactions ON posts.id = reactions.post_id
-        WHERE posts.id = $1
-        GROUP BY posts.id, users.username, posts.title, posts.content, posts.created_at, posts.updated_at, users.id, profiles.profile_image",postid
-    )
-    .fetch_optional(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?
-    .ok_or( AppError::JsendFail(json!({"post" : "post doesnt exist"})))?;
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+    let post = data
+        .repos
+        .posts
+        .find(postid, viewer_id)
+        .await?
+        .ok_or(AppError::NotFound(json!({"post" : "post doesnt exist"})))?;
     let response = JsendResponse::success(Some(json!({
         "post": post
     })));
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/posts/{post_id}",
+    tag = "posts",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    responses(
+        (status = 200, description = "Post deleted", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Not the author or a moderator", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn delete_post(
     Extension(user): Extension<UserModel>,
     State(data): State<Arc<AppState>>,
     AppPath(postid): AppPath<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let post_id = Uuid::parse_str(&postid)
-        .map_err(|_| AppError::JsendFail(json!({"post_id" : "not a valid UUID"})))?;
-    let post_uuid = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", post_id)
-        .fetch_optional(&data.db)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+    let post_uuid = data.repos.posts.author(post_id).await?;
 
     let post_uuid = match post_uuid {
         Some(val) => val,
         None => {
-            return Err(AppError::JsendFail(
+            return Err(AppError::NotFound(
                 json!({"data" : { "post" : "post does not exist"}}),
             ))
         }
     };
 
-    if user.id != Some(post_uuid) {
-        return Err(AppError::JsendFail(
+    if user.id != Some(post_uuid) && !posts::is_moderator(&user) {
+        return Err(AppError::Forbidden(
             json!({"authorization" : "user not authorized to delete this"}),
         ));
     }
-    sqlx::query!("DELETE FROM posts WHERE id = $1", post_id)
-        .execute(&data.db)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+    // Posts are only hidden here; they are purged once the retention period
+    // has passed, so they can be restored until then.
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+    // Someone else may have deleted it in the meantime.
+    if !data.repos.posts.soft_delete(post_id, user_id).await? {
+        return Err(AppError::NotFound(
+            json!({"data" : { "post" : "post does not exist"}}),
+        ));
+    }
 
     let response = JsendResponse::success(None);
 
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/posts/{post_id}/restore",
+    tag = "posts",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    responses(
+        (status = 200, description = "Post restored", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Not allowed to restore the post", body = JsendResponse),
+        (status = 404, description = "Deleted post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn restore_post(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(postid): AppPath<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let post_id = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+
+    let post = data
+        .repos
+        .posts
+        .find_deleted(post_id, data.env.post_retention_days)
+        .await?
+        .ok_or(AppError::NotFound(
+            json!({"post" : "no deleted post to restore"}),
+        ))?;
+
+    // Authors cannot undo a moderator's deletion of their post.
+    let is_author = user.id == Some(post.user_id) && post.deleted_by == user.id;
+    if !is_author && !posts::is_moderator(&user) {
+        return Err(AppError::Forbidden(
+            json!({"authorization" : "user not authorized to restore this"}),
+        ));
+    }
+
+    data.repos.posts.restore(post_id).await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "post_id": post_id
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/posts/{post_id}/react",
+    tag = "posts",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    request_body = LikePostSchema,
+    responses(
+        (status = 200, description = "Reaction saved", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Post author is blocked", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn react_to_post(
     State(data): State<Arc<AppState>>,
     Extension(user): Extension<UserModel>,
@@ -90,51 +165,47 @@ pub async fn react_to_post(
 ) -> Result<impl IntoResponse, AppError> {
     is_like.validate()?;
     let post_id = Uuid::parse_str(&postid)
-        .map_err(|_| AppError::JsendFail(json!({"post_id" : "not a valid UUID"})))?;
-
-    let existing_reaction = sqlx::query!(
-        "SELECT id FROM reactions WHERE post_id = $1 AND user_id = $2",
-        post_id,
-        user.id
-    )
-    .fetch_optional(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
-
-    if let Some(reaction) = existing_reaction {
-        // Update the existing reaction
-        sqlx::query!(
-            "UPDATE reactions SET reaction_type = $1 WHERE id = $2",
-            is_like.is_like,
-            reaction.id
-        )
-        .execute(&data.db)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
-    } else {
-        // Insert a new reaction
-        sqlx::query!(
-            "INSERT INTO reactions (post_id, user_id,  reaction_type) VALUES ($1, $2, $3)",
-            post_id,
-            user.id,
-            is_like.is_like
-        )
-        .execute(&data.db)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
+
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
+
+    let author_id = data
+        .repos
+        .posts
+        .author(post_id)
+        .await?
+        .ok_or(AppError::NotFound(json!({"post" : "post doesnt exist"})))?;
+
+    let blocked = data
+        .repos
+        .users
+        .is_blocked_between(user_id, author_id)
+        .await?;
+    if blocked {
+        return Err(AppError::Forbidden(
+            json!({"post" : "cannot react to posts from a blocked user"}),
+        ));
     }
 
-    let counts = sqlx::query!(
-        "SELECT 
-            COALESCE(SUM(CASE WHEN reaction_type = TRUE THEN 1 ELSE 0 END), 0) AS likes,
-            COALESCE(SUM(CASE WHEN reaction_type = FALSE THEN 1 ELSE 0 END), 0) AS dislikes
-        FROM reactions
-        WHERE post_id = $1",
-        post_id
-    )
-    .fetch_one(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+    let counts = data
+        .repos
+        .reactions
+        .react(post_id, author_id, user_id, is_like.is_like)
+        .await?;
+
+    data.events
+        .publish(
+            Channel::Post(post_id),
+            Event::Reactions {
+                post_id,
+                like_count: counts.likes,
+                dislike_count: counts.dislikes,
+            },
+        )
+        .await;
+    if let Some(notification) = &counts.notification {
+        notifications::publish(&data.events, notification).await;
+    }
 
     let response = JsendResponse::success(Some(json!({
             "post_id": post_id,
@@ -144,55 +215,116 @@ pub async fn react_to_post(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/posts",
+    tag = "posts",
+    responses(
+        (status = 200, description = "All posts", body = JsendResponse),
+    ),
+)]
 pub async fn get_all_posts(
+    viewer: Option<Extension<UserModel>>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, AppError> {
-    let posts: Vec<PostResponse> = sqlx::query_as!(
-        PostResponse,
-        "SELECT
-            posts.id,
-            users.username,
-            posts.title,
-            posts.content,
-            posts.created_at,
-            posts.updated_at,
-            posts.user_id,
-            profiles.profile_image,
-            COALESCE(SUM(CASE WHEN reactions.reaction_type = TRUE THEN 1 ELSE 0 END), 0) AS likes,
-            COALESCE(SUM(CASE WHEN reactions.reaction_type = FALSE THEN 1 ELSE 0 END), 0) AS dislikes
-        FROM posts
-        JOIN users ON posts.user_id = users.id
-        JOIN profiles ON profiles.user_id = users.id
-        LEFT JOIN reactions ON posts.id = reactions.post_id
-        GROUP BY posts.id, users.username, posts.title, posts.content, posts.created_at, posts.updated_at, users.id, profiles.profile_image
-        ORDER BY posts.created_at DESC"
-    )
-    .fetch_all(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+    let viewer_id = viewer.and_then(|Extension(viewer)| viewer.id);
+    let posts = data.repos.posts.list(viewer_id).await?;
     let response = JsendResponse::success(Some(json!({
         "posts" : posts
     })));
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/posts",
+    tag = "posts",
+    request_body = CreatePostSchema,
+    responses(
+        (status = 200, description = "Post created", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn create_post(
     Extension(user): Extension<UserModel>,
     State(data): State<Arc<AppState>>,
     AppJson(post): AppJson<CreatePostSchema>,
 ) -> Result<impl IntoResponse, AppError> {
-    post.validate()?;
-    sqlx::query!(
-        "INSERT INTO posts (user_id,title,content) VALUES ($1,$2,$3)",
-        user.id,
-        post.title,
-        post.content
-    )
-    .execute(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+    post.validate_with_args(&data.env.limits)?;
+    let user_id = user.id.ok_or(AppError::InternalServerError)?;
 
-    let response = JsendResponse::success(None);
+    let (post_id, mentioned) = data
+        .repos
+        .posts
+        .create(user_id, &post.title, &post.content)
+        .await?;
+
+    for notification in &mentioned {
+        notifications::publish(&data.events, notification).await;
+    }
+
+    let response = JsendResponse::success(Some(json!({
+        "post_id": post_id
+    })));
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/posts/{post_id}",
+    tag = "posts",
+    params(("post_id" = Uuid, Path, description = "Post ID")),
+    request_body = CreatePostSchema,
+    responses(
+        (status = 200, description = "Post updated", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+        (status = 403, description = "Not the author", body = JsendResponse),
+        (status = 404, description = "Post does not exist", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
+pub async fn update_post(
+    Extension(user): Extension<UserModel>,
+    State(data): State<Arc<AppState>>,
+    AppPath(postid): AppPath<String>,
+    AppJson(post): AppJson<CreatePostSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    post.validate_with_args(&data.env.limits)?;
+    let post_id = Uuid::parse_str(&postid)
+        .map_err(|_| AppError::BadRequest(json!({"post_id" : "not a valid UUID"})))?;
 
+    let author_id = data
+        .repos
+        .posts
+        .author(post_id)
+        .await?
+        .ok_or(AppError::NotFound(json!({"post" : "post doesnt exist"})))?;
+
+    if user.id != Some(author_id) {
+        return Err(AppError::Forbidden(
+            json!({"authorization" : "user not authorized to edit this"}),
+        ));
+    }
+
+    let mentioned = data
+        .repos
+        .posts
+        .update(post_id, author_id, &post.title, &post.content)
+        .await?
+        // The post may have been deleted since it was looked up.
+        .ok_or(AppError::NotFound(json!({"post" : "post doesnt exist"})))?;
+
+    for notification in &mentioned {
+        notifications::publish(&data.events, notification).await;
+    }
+
+    let response = JsendResponse::success(Some(json!({
+        "post_id": post_id
+    })));
     Ok(Json(response))
 }
@@ -1,5 +1,7 @@
 use crate::{
-    model::{ProfileModel, UserModel},
+    accounts::ASSETS_DIR,
+    metrics,
+    model::UserModel,
     response::{AppError, AppPath, JsendResponse},
     AppState,
 };
@@ -14,42 +16,44 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+#[utoipa::path(
+    get,
+    path = "/user/{username}",
+    tag = "users",
+    params(("username" = String, Path, description = "Username")),
+    responses(
+        (status = 200, description = "Profile of the user", body = JsendResponse),
+        (status = 404, description = "User does not exist", body = JsendResponse),
+    ),
+)]
 pub async fn get_profile(
     AppPath(username): AppPath<String>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, AppError> {
     // Query to find the user by username
-    let user_id: Option<uuid::Uuid> =
-        sqlx::query_scalar!("SELECT id FROM users WHERE username = $1", username)
-            .fetch_optional(&data.db)
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+    let user_id = data
+        .repos
+        .users
+        .find_by_username(&username)
+        .await?
+        .and_then(|user| user.id);
 
     // If user is not found, return 404 Not Found
     let user_id = match user_id {
         Some(id) => id,
         _ => {
-            return Err(AppError::JsendError("User not found".to_string()));
+            return Err(AppError::NotFound(json!({"username" : "user not found"})));
         }
     };
 
     // Query to find the profile by user_id
-    let profile: Option<ProfileModel> = sqlx::query_as!(
-        ProfileModel,
-        "SELECT id, user_id, profile_image, bio, created_at, updated_at FROM profiles WHERE user_id = $1",
-        user_id
-    )
-    .fetch_optional(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+    let profile = data.repos.profiles.find_by_user(user_id).await?;
 
     // If profile is not found, return 404 Not Found
     let profile = match profile {
         Some(profile) => profile,
         None => {
-            return Err(AppError::JsendFail(
-                json!({"profile" : "profile not found"}),
-            ));
+            return Err(AppError::NotFound(json!({"profile" : "profile not found"})));
         }
     };
 
@@ -57,55 +61,65 @@ pub async fn get_profile(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/profile/upload",
+    tag = "users",
+    request_body(content_type = "multipart/form-data", description = "A PNG or JPEG image"),
+    responses(
+        (status = 200, description = "Profile picture updated", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 401, description = "Not logged in", body = JsendResponse),
+    ),
+    security(("session" = [])),
+)]
 pub async fn upload_profile_pic(
     Extension(user): Extension<UserModel>,
     State(data): State<Arc<AppState>>,
     mut multipart: Multipart,
 ) -> Result<impl IntoResponse, AppError> {
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|_| AppError::InternalServerError)?
-    {
+    while let Some(field) = multipart.next_field().await? {
         let content_type = match field.content_type() {
             Some(content) => content.to_string(),
-            None => return Err(AppError::JsendError("invalid content-type".into())),
+            None => {
+                return Err(AppError::BadRequest(
+                    json!({"file" : "missing content type"}),
+                ))
+            }
         };
 
         let file_extension = match content_type.as_str() {
             "image/png" => "png",
             "image/jpeg" => "jpg",
-            _ => return Err(AppError::JsendError("Unsupported file type".into())),
+            _ => {
+                return Err(AppError::BadRequest(
+                    json!({"file" : "unsupported file type"}),
+                ))
+            }
         };
 
         let bdata = match field.bytes().await {
             Ok(bdata) => bdata,
-            Err(_) => return Err(AppError::JsendError("data error".into())),
+            Err(_) => {
+                return Err(AppError::BadRequest(
+                    json!({"file" : "could not read file data"}),
+                ))
+            }
         };
+        metrics::record_upload(bdata.len());
 
-        let uuid_string = match user.id {
-            Some(id) => id.to_string(),
-            None => return Err(AppError::InternalServerError),
-        };
+        let user_id = user.id.ok_or(AppError::InternalServerError)?;
 
-        let assets_dir = PathBuf::from("./assets");
+        let assets_dir = PathBuf::from(ASSETS_DIR);
 
-        let file_name = format!("{}.{}", uuid_string, file_extension);
+        let file_name = format!("{}.{}", user_id, file_extension);
 
         let file_path = assets_dir.join(&file_name);
 
-        let mut file = File::create(file_path).map_err(|_| AppError::JsendError("DATA".into()))?;
-        file.write_all(&bdata)
-            .map_err(|_| AppError::InternalServerError)?;
+        let mut file = File::create(file_path)?;
+        file.write_all(&bdata)?;
 
-        sqlx::query!(
-            "UPDATE profiles SET profile_image = $1 WHERE user_id = $2",
-            file_name,
-            user.id
-        )
-        .execute(&data.db)
-        .await
-        .map_err(|_| AppError::InternalServerError)?;
+        data.repos.profiles.set_image(user_id, &file_name).await?;
     }
 
     let response = JsendResponse::success(None);
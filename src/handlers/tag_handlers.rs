@@ -0,0 +1,116 @@
+use crate::{
+    model::{Mentions, PostResponse, TrendingTagResponse, UserModel},
+    response::{AppError, AppPath, AppQuery, JsendResponse},
+    schema::{PaginationSchema, TrendingTagsSchema},
+    tags::normalize_tag,
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde_json::json;
+use std::sync::Arc;
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/tags/{tag}",
+    tag = "tags",
+    params(
+        ("tag" = String, Path, description = "Tag name without the leading #"),
+        PaginationSchema,
+    ),
+    responses(
+        (status = 200, description = "Posts with the tag", body = JsendResponse),
+        (status = 400, description = "Invalid request", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+    ),
+)]
+pub async fn get_tag_posts(
+    viewer: Option<Extension<UserModel>>,
+    State(data): State<Arc<AppState>>,
+    AppPath(tag): AppPath<String>,
+    AppQuery(pagination): AppQuery<PaginationSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    pagination.validate()?;
+    let viewer_id = viewer.and_then(|Extension(viewer)| viewer.id);
+    let tag = normalize_tag(&tag)
+        .ok_or_else(|| AppError::BadRequest(json!({"tag" : "not a valid tag"})))?;
+
+    let posts: Vec<PostResponse> = sqlx::query_as!(
+        PostResponse,
+        r#"SELECT
+            posts.id,
+            users.username,
+            posts.title,
+            posts.content,
+            posts.created_at,
+            posts.updated_at,
+            posts.user_id,
+            profiles.profile_image,
+            posts.like_count AS likes,
+            posts.dislike_count AS dislikes,
+            posts.comment_count AS comments,
+            post_mentions(posts.id) AS "mentions!: Mentions"
+        FROM tags
+        JOIN post_tags ON post_tags.tag_id = tags.id
+        JOIN posts ON post_tags.post_id = posts.id
+        JOIN users ON posts.user_id = users.id
+        JOIN profiles ON profiles.user_id = users.id
+        WHERE tags.name = $1 AND posts.deleted_at IS NULL AND NOT is_hidden_from(posts.user_id, $4)
+        ORDER BY posts.created_at DESC
+        LIMIT $2 OFFSET $3"#,
+        tag,
+        pagination.limit(),
+        pagination.offset(),
+        viewer_id
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "tag": tag,
+        "posts": posts,
+        "page": pagination.page,
+        "per_page": pagination.per_page,
+    })));
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/trending",
+    tag = "tags",
+    params(TrendingTagsSchema),
+    responses(
+        (status = 200, description = "Most used tags in the time window", body = JsendResponse),
+        (status = 422, description = "Validation failed", body = JsendResponse),
+    ),
+)]
+pub async fn get_trending_tags(
+    State(data): State<Arc<AppState>>,
+    AppQuery(query): AppQuery<TrendingTagsSchema>,
+) -> Result<impl IntoResponse, AppError> {
+    query.validate()?;
+    let tags: Vec<TrendingTagResponse> = sqlx::query_as!(
+        TrendingTagResponse,
+        r#"SELECT
+            tags.name,
+            COUNT(*) AS "post_count!"
+        FROM post_tags
+        JOIN tags ON post_tags.tag_id = tags.id
+        JOIN posts ON post_tags.post_id = posts.id
+        WHERE posts.created_at > NOW() - make_interval(hours => $1) AND posts.deleted_at IS NULL
+        GROUP BY tags.name
+        ORDER BY COUNT(*) DESC, tags.name
+        LIMIT $2"#,
+        query.hours,
+        query.limit
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    let response = JsendResponse::success(Some(json!({
+        "tags": tags,
+        "hours": query.hours,
+    })));
+    Ok(Json(response))
+}
@@ -1,5 +1,4 @@
 use crate::{
-    model::{ProfileResponse, UserResponse},
     response::{AppError, JsendResponse},
     AppState,
 };
@@ -7,16 +6,18 @@ use axum::{extract::State, response::IntoResponse, Json};
 use serde_json::json;
 use std::sync::Arc;
 
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    responses(
+        (status = 200, description = "All users", body = JsendResponse),
+    ),
+)]
 pub async fn get_all_users(
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, AppError> {
-    let users: Vec<ProfileResponse> = sqlx::query_as!(
-        ProfileResponse,
-        "SELECT profiles.id AS profile_id, profiles.profile_image, username FROM users JOIN profiles on users.id = profiles.user_id"
-    )
-    .fetch_all(&data.db)
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+    let users = data.repos.profiles.list().await?;
 
     let response = JsendResponse::success(Some(json!({"users" : users})));
     Ok(Json(response))
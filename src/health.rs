@@ -0,0 +1,62 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tower_sessions_redis_store::fred::prelude::*;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tracks whether the server should receive traffic. It stops being ready as
+/// soon as shutdown starts, so load balancers drain it before it exits.
+#[derive(Clone, Default)]
+pub struct Readiness {
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    pub fn start_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    pub healthy: bool,
+    pub latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub async fn check_postgres(db: &Pool<Postgres>) -> DependencyStatus {
+    check(async { sqlx::query("SELECT 1").execute(db).await.map(|_| ()) }).await
+}
+
+pub async fn check_redis(redis: &RedisPool) -> DependencyStatus {
+    check(async { redis.next().ping::<()>().await }).await
+}
+
+async fn check<E: std::fmt::Display>(
+    probe: impl Future<Output = Result<(), E>>,
+) -> DependencyStatus {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("timed out after {:?}", CHECK_TIMEOUT)),
+    };
+    DependencyStatus {
+        healthy: error.is_none(),
+        latency_ms: started.elapsed().as_millis(),
+        error,
+    }
+}
@@ -0,0 +1,49 @@
+pub mod accounts;
+pub mod admin;
+pub mod blocks;
+pub mod commands;
+pub mod config;
+pub mod events;
+pub mod exports;
+pub mod filters;
+pub mod handlers;
+pub mod health;
+pub mod mentions;
+pub mod metrics;
+pub mod migrations;
+pub mod model;
+pub mod notifications;
+pub mod openapi;
+pub mod posts;
+pub mod repo;
+pub mod request_id;
+pub mod response;
+pub mod route;
+pub mod schema;
+pub mod seed;
+pub mod session_auth;
+pub mod shutdown;
+pub mod tags;
+pub mod tasks;
+pub mod validation;
+
+use config::Config;
+use events::EventBus;
+use health::Readiness;
+use metrics_exporter_prometheus::PrometheusHandle;
+use repo::Repos;
+use sqlx::{Pool, Postgres};
+use tokio::sync::watch;
+use tower_sessions_redis_store::fred::prelude::RedisPool;
+
+pub struct AppState {
+    pub db: Pool<Postgres>,
+    pub repos: Repos,
+    pub env: Config,
+    pub events: EventBus,
+    pub redis: Option<RedisPool>,
+    pub readiness: Readiness,
+    pub metrics: PrometheusHandle,
+    /// Flips to `true` when shutdown starts; see [`shutdown::requested`].
+    pub shutdown: watch::Receiver<bool>,
+}
@@ -1,42 +1,91 @@
-mod config;
-mod filters;
-mod handlers;
-mod model;
-mod response;
-mod route;
-mod schema;
-mod session_auth;
-mod validation;
-
 use axum::http::{
     header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
     HeaderValue, Method,
 };
-use config::Config;
+use axum::middleware;
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use route::create_router;
+use server::{
+    commands,
+    config::Config,
+    events::EventBus,
+    health::Readiness,
+    metrics, migrations,
+    repo::Repos,
+    request_id::{self, REQUEST_ID_HEADER},
+    route::{create_metrics_router, create_router, DEPRECATION_HEADERS},
+    shutdown, tasks, AppState,
+};
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use std::future::IntoFuture;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration as StdDuration;
 use time::Duration;
 use tower_http::trace::TraceLayer;
 use tower_http::{cors::CorsLayer, services::ServeDir};
 use tower_sessions::{cookie::SameSite, Expiry, SessionManagerLayer};
 use tower_sessions_redis_store::{fred::prelude::*, RedisStore};
 
-#[allow(dead_code)]
-pub struct AppState {
-    db: Pool<Postgres>,
-    env: Config,
+/// Serves the API, or runs a maintenance command and exits.
+#[derive(Parser)]
+#[command(name = "server")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Recompute post like, dislike and comment counters.
+    RepairCounters,
+    /// Apply, revert or list database migrations.
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply pending migrations.
+    Up,
+    /// Revert the most recent migrations.
+    Down {
+        #[arg(default_value_t = NonZeroUsize::MIN)]
+        steps: NonZeroUsize,
+    },
+    /// List applied and pending migrations.
+    Status,
 }
 
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
     dotenv().ok();
 
-    let config = Config::init();
+    let config = match Config::init() {
+        Ok(config) => config,
+        Err(err) => {
+            println!("🔥 Invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(config.log_level)
+        .init();
+
+    let metrics = match metrics::install() {
+        Ok(metrics) => metrics,
+        Err(err) => {
+            println!("🔥 Failed to install the metrics recorder: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
     let pool = match PgPoolOptions::new()
-        .max_connections(10)
+        .max_connections(config.database_max_connections)
         .connect(&config.database_url)
         .await
     {
@@ -50,7 +99,42 @@ async fn main() {
         }
     };
 
-    let redis_pool = match RedisPool::new(RedisConfig::default(), None, None, None, 6) {
+    match cli.command {
+        Some(Command::RepairCounters) => match commands::repair_counters(&pool).await {
+            Ok(repaired) => {
+                println!("✅ Repaired counters on {} posts", repaired);
+                std::process::exit(0);
+            }
+            Err(err) => {
+                println!("🔥 Failed to repair counters: {:?}", err);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Migrate { command }) => {
+            std::process::exit(migrate_command(&pool, command).await);
+        }
+        None => {}
+    }
+
+    if config.run_migrations {
+        match migrations::up(&pool).await {
+            Ok(()) => println!("✅ Migrations applied"),
+            Err(err) => {
+                println!("🔥 Failed to run migrations: {:?}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // The URL is checked when the configuration is loaded.
+    let redis_config = RedisConfig::from_url(&config.redis_url).expect("invalid REDIS_URL");
+    let redis_pool = match RedisPool::new(
+        redis_config.clone(),
+        None,
+        None,
+        None,
+        config.redis_pool_size,
+    ) {
         Ok(pool) => {
             println!("✅ Connection to redis successfull!");
             pool
@@ -65,32 +149,197 @@ async fn main() {
 
     let redis_conn = redis_pool.connect();
 
-    let session_store = RedisStore::new(redis_pool);
+    let events = EventBus::new(Some(redis_pool.clone()));
+    if let Err(err) = events.listen(redis_config).await {
+        println!("🔥 Failed to subscribe to redis events: {:?}", err);
+        std::process::exit(1);
+    }
+
+    let session_store = RedisStore::new(redis_pool.clone());
     let session_layer = SessionManagerLayer::new(session_store)
-        .with_secure(false)
+        .with_secure(config.session_secure)
         .with_same_site(SameSite::Lax)
-        .with_expiry(Expiry::OnInactivity(Duration::hours(12)));
+        .with_expiry(Expiry::OnInactivity(Duration::hours(
+            config.session_expiry_hours,
+        )));
+
+    let origins = match config
+        .cors_origins
+        .iter()
+        .map(|origin| origin.parse::<HeaderValue>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(origins) => origins,
+        Err(err) => {
+            println!("🔥 Invalid CORS origin: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
     let cors = CorsLayer::new()
-        .allow_origin("http://localhost:5173".parse::<HeaderValue>().unwrap())
+        .allow_origin(origins)
         .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
         .allow_credentials(true)
-        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
+        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, REQUEST_ID_HEADER])
+        .expose_headers(
+            [REQUEST_ID_HEADER]
+                .into_iter()
+                .chain(DEPRECATION_HEADERS)
+                .collect::<Vec<_>>(),
+        );
 
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
-        .init();
+    tasks::spawn_purge_task(pool.clone(), config.post_retention_days);
+
+    tasks::spawn_metrics_upkeep(metrics.clone());
 
-    let app = create_router(Arc::new(AppState {
+    let readiness = Readiness::default();
+    let shutdown_requested = shutdown::listen(
+        readiness.clone(),
+        StdDuration::from_secs(config.shutdown_readiness_delay_secs),
+    );
+
+    let app_state = Arc::new(AppState {
         db: pool.clone(),
+        repos: Repos::postgres(pool.clone()),
         env: config.clone(),
-    }))
-    .nest_service("/assets", ServeDir::new("./assets"))
-    .layer(cors)
-    .layer(session_layer)
-    .layer(TraceLayer::new_for_http());
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
-    redis_conn.await.unwrap().unwrap();
+        events,
+        redis: Some(redis_pool.clone()),
+        readiness,
+        metrics,
+        shutdown: shutdown_requested.clone(),
+    });
+
+    let app = create_router(app_state.clone())
+        .nest_service("/assets", ServeDir::new("./assets"))
+        .layer(cors)
+        .layer(session_layer)
+        .layer(TraceLayer::new_for_http())
+        .layer(middleware::from_fn(request_id::request_id));
+
+    let listener = match tokio::net::TcpListener::bind(config.bind_address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            println!("🔥 Failed to bind {}: {:?}", config.bind_address, err);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(address) = config.metrics_bind_address {
+        let listener = match tokio::net::TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                println!("🔥 Failed to bind {}: {:?}", address, err);
+                std::process::exit(1);
+            }
+        };
+        tokio::spawn(
+            axum::serve(listener, create_metrics_router(app_state))
+                .with_graceful_shutdown(shutdown::requested(shutdown_requested.clone()))
+                .into_future(),
+        );
+    }
+
+    let mut server = tokio::spawn(
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown::requested(shutdown_requested.clone()))
+            .into_future(),
+    );
+
+    // Once shutdown is requested, in-flight requests get until the deadline
+    // to finish before the server is stopped.
+    let deadline = StdDuration::from_secs(config.shutdown_timeout_secs);
+    let served = tokio::select! {
+        served = &mut server => Some(served),
+        () = shutdown::requested(shutdown_requested) => {
+            tokio::time::timeout(deadline, &mut server).await.ok()
+        }
+    };
+    let mut clean = match served {
+        Some(Ok(Ok(()))) => true,
+        Some(Ok(Err(err))) => {
+            println!("🔥 Server stopped with an error: {:?}", err);
+            false
+        }
+        Some(Err(err)) => {
+            println!("🔥 Server task failed: {:?}", err);
+            false
+        }
+        None => {
+            println!("🔥 Requests did not drain within {:?}", deadline);
+            server.abort();
+            false
+        }
+    };
+
+    pool.close().await;
+    if let Err(err) = redis_pool.quit().await {
+        println!("🔥 Failed to close redis connections: {:?}", err);
+        clean = false;
+    }
+    match redis_conn.await {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            println!("🔥 Redis connection closed with an error: {:?}", err);
+            clean = false;
+        }
+        Err(err) => {
+            println!("🔥 Redis connection task failed: {:?}", err);
+            clean = false;
+        }
+    }
+
+    if clean {
+        println!("✅ Server stopped cleanly");
+        std::process::exit(0);
+    }
+    std::process::exit(1);
+}
+
+/// Runs a `migrate` subcommand and returns the exit code.
+async fn migrate_command(pool: &Pool<Postgres>, command: MigrateCommand) -> i32 {
+    match command {
+        MigrateCommand::Up => match migrations::up(pool).await {
+            Ok(()) => {
+                println!("✅ Migrations applied");
+                0
+            }
+            Err(err) => {
+                println!("🔥 Failed to run migrations: {:?}", err);
+                1
+            }
+        },
+        MigrateCommand::Down { steps } => match migrations::down(pool, steps.get()).await {
+            Ok(reverted) if reverted.is_empty() => {
+                println!("✅ No migrations to revert");
+                0
+            }
+            Ok(reverted) => {
+                for version in reverted {
+                    println!("✅ Reverted {}", version);
+                }
+                0
+            }
+            Err(err) => {
+                println!("🔥 Failed to revert migrations: {:?}", err);
+                1
+            }
+        },
+        MigrateCommand::Status => match migrations::status(pool).await {
+            Ok(statuses) => {
+                for status in statuses {
+                    println!(
+                        "{:<10} {} {}",
+                        format!("{:?}", status.state).to_lowercase(),
+                        status.version,
+                        status.description
+                    );
+                }
+                0
+            }
+            Err(err) => {
+                println!("🔥 Failed to read migration status: {:?}", err);
+                1
+            }
+        },
+    }
 }
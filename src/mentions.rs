@@ -0,0 +1,240 @@
+use crate::notifications::{self, NewNotification, NotificationKind};
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const MAX_USERNAME_LENGTH: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub struct MentionToken {
+    pub username: String,
+    /// Offset of the `@` in code points from the start of the text.
+    pub start: i32,
+    /// Offset one past the last character of the username.
+    pub end: i32,
+}
+
+pub enum MentionSource {
+    Post { post_id: Uuid },
+    Comment { post_id: Uuid, comment_id: Uuid },
+}
+
+/// Finds every `@username` in a piece of text. A mention starts with `@` at
+/// the beginning of the text or after a non-word character, so email
+/// addresses are not picked up. Offsets are counted in code points, not
+/// UTF-16 units.
+pub fn extract_mentions(content: &str) -> Vec<MentionToken> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut mentions = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let preceded_by_word = i > 0 && is_word_char(chars[i - 1]);
+        if chars[i] != '@' || preceded_by_word {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut end = i + 1;
+        while end < chars.len() && is_username_char(chars[end]) {
+            end += 1;
+        }
+        // Trailing punctuation ends a sentence rather than a username.
+        while end > start + 1 && matches!(chars[end - 1], '.' | '-') {
+            end -= 1;
+        }
+
+        let len = end - start - 1;
+        if len > 0 && len <= MAX_USERNAME_LENGTH {
+            mentions.push(MentionToken {
+                username: chars[start + 1..end].iter().collect(),
+                start: start as i32,
+                end: end as i32,
+            });
+        }
+        i = end.max(start + 1);
+    }
+    mentions
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_username_char(c: char) -> bool {
+    is_word_char(c) || c == '.' || c == '-'
+}
+
+/// Replaces the mentions stored for a post or comment with the ones found in
+/// `content`, and notifies users who were not mentioned there before.
+/// Returns the notifications that were stored.
+pub async fn save_mentions(
+    conn: &mut PgConnection,
+    actor_id: Uuid,
+    source: MentionSource,
+    content: &str,
+) -> Result<Vec<NewNotification>, sqlx::Error> {
+    store(conn, actor_id, source, content, None).await
+}
+
+/// Like [`save_mentions`], but dated `created_at` instead of now, for
+/// generated data.
+pub async fn save_mentions_at(
+    conn: &mut PgConnection,
+    actor_id: Uuid,
+    source: MentionSource,
+    content: &str,
+    created_at: DateTime<Utc>,
+) -> Result<Vec<NewNotification>, sqlx::Error> {
+    store(conn, actor_id, source, content, Some(created_at)).await
+}
+
+async fn store(
+    conn: &mut PgConnection,
+    actor_id: Uuid,
+    source: MentionSource,
+    content: &str,
+    created_at: Option<DateTime<Utc>>,
+) -> Result<Vec<NewNotification>, sqlx::Error> {
+    let (post_id, comment_id, notify_post_id) = match source {
+        MentionSource::Post { post_id } => (Some(post_id), None, post_id),
+        MentionSource::Comment {
+            post_id,
+            comment_id,
+        } => (None, Some(comment_id), post_id),
+    };
+
+    let previous: Vec<Uuid> = sqlx::query_scalar!(
+        "DELETE FROM mentions WHERE post_id = $1 OR comment_id = $2 RETURNING user_id",
+        post_id,
+        comment_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let tokens = extract_mentions(content);
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut usernames: Vec<String> = tokens.iter().map(|t| t.username.clone()).collect();
+    usernames.sort();
+    usernames.dedup();
+    let users: HashMap<String, Uuid> = sqlx::query!(
+        "SELECT id, username FROM users WHERE username = ANY($1)",
+        &usernames
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|user| (user.username, user.id))
+    .collect();
+
+    let mut user_ids = Vec::new();
+    let mut starts = Vec::new();
+    let mut ends = Vec::new();
+    for token in &tokens {
+        if let Some(user_id) = users.get(&token.username) {
+            user_ids.push(*user_id);
+            starts.push(token.start);
+            ends.push(token.end);
+        }
+    }
+
+    sqlx::query!(
+        "INSERT INTO mentions (user_id, post_id, comment_id, start_offset, end_offset, created_at)
+        SELECT mentioned.user_id, $2, $3, mentioned.start_offset, mentioned.end_offset, COALESCE($6, NOW())
+        FROM UNNEST($1::UUID[], $4::INT[], $5::INT[]) AS mentioned(user_id, start_offset, end_offset)",
+        &user_ids,
+        post_id,
+        comment_id,
+        &starts,
+        &ends,
+        created_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let mut notified: Vec<NewNotification> = Vec::new();
+    for user_id in user_ids {
+        if previous.contains(&user_id) || notified.iter().any(|n| n.user_id == user_id) {
+            continue;
+        }
+        let notification = NewNotification {
+            user_id,
+            actor_id,
+            kind: NotificationKind::Mention,
+            post_id: Some(notify_post_id),
+            comment_id,
+        };
+        let stored = match created_at {
+            Some(created_at) => notifications::notify_at(conn, &notification, created_at).await?,
+            None => notifications::notify(conn, &notification).await?,
+        };
+        if stored {
+            notified.push(notification);
+        }
+    }
+
+    Ok(notified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_mentions, MentionToken, MAX_USERNAME_LENGTH};
+
+    fn usernames(content: &str) -> Vec<String> {
+        extract_mentions(content)
+            .into_iter()
+            .map(|token| token.username)
+            .collect()
+    }
+
+    #[test]
+    fn records_offsets_in_code_points() {
+        assert_eq!(
+            extract_mentions("héllo @bob"),
+            [MentionToken {
+                username: "bob".to_string(),
+                start: 6,
+                end: 10,
+            }]
+        );
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_a_username() {
+        assert_eq!(
+            usernames("thanks @bob. and @carol- or @dave.smith, (@erin)!"),
+            ["bob", "carol", "dave.smith", "erin"]
+        );
+        assert!(usernames("just @ or @. here").is_empty());
+    }
+
+    #[test]
+    fn ignores_email_addresses() {
+        assert!(usernames("write to a@b.com or bob_@example.org").is_empty());
+        assert_eq!(usernames("a@b.com, cc @b"), ["b"]);
+    }
+
+    #[test]
+    fn keeps_every_occurrence_as_typed() {
+        let tokens = extract_mentions("@Bob @bob @Bob");
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (token.username.as_str(), token.start))
+                .collect::<Vec<_>>(),
+            [("Bob", 0), ("bob", 5), ("Bob", 10)]
+        );
+    }
+
+    #[test]
+    fn skips_names_longer_than_a_username() {
+        let longest = "a".repeat(MAX_USERNAME_LENGTH);
+        assert_eq!(usernames(&format!("@{}", longest)), [longest.as_str()]);
+        assert!(usernames(&format!("@{}a", longest)).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{response::AppError, AppState};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use serde_json::json;
+use std::{sync::Arc, time::Instant};
+use tower_sessions_redis_store::fred::prelude::*;
+
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+const UPLOAD_SIZE: &str = "upload_size_bytes";
+
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+const UPLOAD_SIZE_BUCKETS: &[f64] = &[
+    16_384.0,
+    65_536.0,
+    262_144.0,
+    1_048_576.0,
+    4_194_304.0,
+    16_777_216.0,
+];
+
+/// Installs the global Prometheus recorder that every metric in the server
+/// is written to.
+pub fn install() -> Result<PrometheusHandle, BuildError> {
+    builder().install_recorder()
+}
+
+/// A recorder that is not installed globally, for states built in tests.
+pub fn detached() -> PrometheusHandle {
+    builder().build_recorder().handle()
+}
+
+fn builder() -> PrometheusBuilder {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(REQUEST_DURATION.to_string()),
+            REQUEST_DURATION_BUCKETS,
+        )
+        .expect("request duration buckets are not empty")
+        .set_buckets_for_metric(Matcher::Full(UPLOAD_SIZE.to_string()), UPLOAD_SIZE_BUCKETS)
+        .expect("upload size buckets are not empty")
+}
+
+/// Counts requests and records their latency, labelled with the route they
+/// matched rather than the raw path so that ids do not create new series.
+pub async fn track_requests(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", format!("{}xx", response.status().as_u16() / 100)),
+    ];
+    ::metrics::counter!("http_requests_total", &labels).increment(1);
+    ::metrics::histogram!(REQUEST_DURATION, &labels).record(elapsed);
+
+    response
+}
+
+/// Rejects scrapes that do not carry the configured bearer token.
+pub async fn require_token(
+    State(token): State<Arc<str>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let provided = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if constant_time_eq(provided.as_bytes(), token.as_bytes()) => {
+            Ok(next.run(req).await)
+        }
+        _ => Err(AppError::Unauthorized(
+            json!({"token" : "missing or invalid metrics token"}),
+        )),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Samples the connection pools and renders every metric in the Prometheus
+/// text format.
+pub fn render(data: &AppState) -> String {
+    let size = data.db.size();
+    let idle = data.db.num_idle() as u32;
+    ::metrics::gauge!("db_pool_connections", "state" => "idle").set(idle);
+    ::metrics::gauge!("db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle));
+    ::metrics::gauge!("db_pool_max_connections").set(data.env.database_max_connections);
+
+    if let Some(redis) = &data.redis {
+        let connected = redis
+            .clients()
+            .iter()
+            .filter(|client| client.is_connected())
+            .count();
+        ::metrics::gauge!("redis_pool_size").set(redis.size() as f64);
+        ::metrics::gauge!("redis_pool_connected").set(connected as f64);
+    }
+
+    data.metrics.render()
+}
+
+pub fn record_login(success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    ::metrics::counter!("logins_total", "outcome" => outcome).increment(1);
+}
+
+pub fn record_upload(bytes: usize) {
+    ::metrics::histogram!(UPLOAD_SIZE).record(bytes as f64);
+}
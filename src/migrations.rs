@@ -0,0 +1,94 @@
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    Pool, Postgres,
+};
+
+/// The migrations in `migrations/`, embedded at compile time.
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file has changed since.
+    Modified,
+    /// Recorded in the database but no longer present in `migrations/`.
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Applies every pending migration. The migrator holds a Postgres advisory
+/// lock while it runs, so instances booting at the same time apply each
+/// migration once and the others wait for it.
+pub async fn up(db: &Pool<Postgres>) -> Result<(), MigrateError> {
+    MIGRATOR.run(db).await
+}
+
+/// Reverts the last `steps` applied migrations, under the same lock as
+/// [`up`]. Returns the versions that were reverted, newest first.
+pub async fn down(db: &Pool<Postgres>, steps: usize) -> Result<Vec<i64>, MigrateError> {
+    let mut applied = applied_versions(db).await?;
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let reverted: Vec<i64> = applied.iter().copied().take(steps).collect();
+    let target = applied.get(steps).copied().unwrap_or(0);
+    MIGRATOR.undo(db, target).await?;
+    Ok(reverted)
+}
+
+/// Compares the embedded migrations with the ones recorded in the database.
+pub async fn status(db: &Pool<Postgres>) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let mut conn = db.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied = conn.list_applied_migrations().await?;
+
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .map(|migration| {
+            let state = match applied.iter().find(|a| a.version == migration.version) {
+                Some(a) if a.checksum == migration.checksum => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+
+    for a in &applied {
+        if !MIGRATOR
+            .iter()
+            .any(|migration| migration.version == a.version)
+        {
+            statuses.push(MigrationStatus {
+                version: a.version,
+                description: String::new(),
+                state: MigrationState::Missing,
+            });
+        }
+    }
+
+    statuses.sort_by_key(|status| status.version);
+    Ok(statuses)
+}
+
+async fn applied_versions(db: &Pool<Postgres>) -> Result<Vec<i64>, MigrateError> {
+    let mut conn = db.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    Ok(conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect())
+}
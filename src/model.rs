@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize, FromRow, Serialize, Clone)]
@@ -12,9 +13,11 @@ pub struct UserModel {
     pub role: String,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub delete_after: Option<DateTime<Utc>>,
+    pub banned_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, FromRow, Serialize, Clone)]
+#[derive(Debug, Deserialize, FromRow, Serialize, Clone, ToSchema)]
 pub struct ProfileModel {
     pub id: Option<Uuid>,
     pub user_id: Uuid,
@@ -49,7 +52,7 @@ pub struct ReactionModel {
     pub id: Option<Uuid>,
     pub user_id: Uuid,
     pub post_id: Uuid,
-    pub reaction_type: String,
+    pub reaction_type: bool,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -80,13 +83,29 @@ pub struct ReactionModel {
 //pub exp: usize,
 //}
 
+#[allow(dead_code)]
 #[derive(Debug, Deserialize, FromRow, Serialize, Clone)]
 pub struct Register {
     pub id: Uuid,
     pub password: String,
 }
 
-#[derive(Serialize)]
+/// A mentioned user and where the `@username` sits in the text. Offsets count
+/// Unicode code points, not UTF-16 code units, so JavaScript clients should
+/// index with `Array.from(text)` rather than `text.slice`.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct MentionEntity {
+    pub user_id: Uuid,
+    pub username: String,
+    /// Code point offset of the `@`.
+    pub start: i32,
+    /// Code point offset one past the end of the username.
+    pub end: i32,
+}
+
+pub type Mentions = Json<Vec<MentionEntity>>;
+
+#[derive(Serialize, ToSchema)]
 pub struct PostResponse {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -94,13 +113,22 @@ pub struct PostResponse {
     pub profile_image: String,
     pub title: String,
     pub content: String,
-    pub likes: Option<i64>,
-    pub dislikes: Option<i64>,
+    pub likes: i64,
+    pub dislikes: i64,
+    pub comments: i64,
+    #[schema(value_type = Vec<MentionEntity>)]
+    pub mentions: Mentions,
     pub updated_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, FromRow, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct TrendingTagResponse {
+    pub name: String,
+    pub post_count: i64,
+}
+
+#[derive(Debug, Deserialize, FromRow, Serialize, Clone, ToSchema)]
 pub struct CommentResponse {
     pub id: Option<Uuid>,
     pub username: String,
@@ -108,11 +136,13 @@ pub struct CommentResponse {
     pub user_id: Uuid,
     pub post_id: Uuid,
     pub content: String,
+    #[schema(value_type = Vec<MentionEntity>)]
+    pub mentions: Mentions,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct UserResponse {
     pub id: Option<Uuid>,
     pub username: String,
@@ -121,7 +151,69 @@ pub struct UserResponse {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct ConversationMember {
+    pub user_id: Uuid,
+    pub username: String,
+}
+
+pub type ConversationMembers = Json<Vec<ConversationMember>>;
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct ConversationResponse {
+    pub id: Uuid,
+    pub is_group: bool,
+    pub title: Option<String>,
+    #[schema(value_type = Vec<ConversationMember>)]
+    pub members: ConversationMembers,
+    pub last_message: Option<String>,
+    pub unread_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct MessageResponse {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub actor_id: Uuid,
+    pub actor_username: String,
+    pub actor_profile_image: String,
+    pub actor_count: i64,
+    pub post_id: Option<Uuid>,
+    pub comment_id: Option<Uuid>,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct RelationshipResponse {
+    pub user_id: Uuid,
+    pub username: String,
+    pub profile_image: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct ProfileResponse {
     pub profile_id: Option<Uuid>,
     pub username: String,
@@ -0,0 +1,108 @@
+use crate::{
+    blocks,
+    events::{Channel, Event, EventBus},
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+pub enum NotificationKind {
+    Like,
+    Comment,
+    Mention,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Like => "like",
+            NotificationKind::Comment => "comment",
+            NotificationKind::Mention => "mention",
+        }
+    }
+}
+
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub actor_id: Uuid,
+    pub kind: NotificationKind,
+    pub post_id: Option<Uuid>,
+    pub comment_id: Option<Uuid>,
+}
+
+/// Stores a notification for `user_id`. Users are never notified about their
+/// own actions or by users they have blocked or been blocked by. Likes and
+/// comments on a post are grouped into the recipient's unread notification
+/// for that post, so they read as "5 people liked your post" rather than
+/// five separate entries. Returns whether a notification was stored.
+pub async fn notify(
+    conn: &mut PgConnection,
+    notification: &NewNotification,
+) -> Result<bool, sqlx::Error> {
+    store(conn, notification, None).await
+}
+
+/// Like [`notify`], but dated `created_at` instead of now, for generated data.
+pub async fn notify_at(
+    conn: &mut PgConnection,
+    notification: &NewNotification,
+    created_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    store(conn, notification, Some(created_at)).await
+}
+
+async fn store(
+    conn: &mut PgConnection,
+    notification: &NewNotification,
+    created_at: Option<DateTime<Utc>>,
+) -> Result<bool, sqlx::Error> {
+    if notification.user_id == notification.actor_id
+        || blocks::is_blocked_between(&mut *conn, notification.user_id, notification.actor_id)
+            .await?
+    {
+        return Ok(false);
+    }
+
+    let notification_id: Uuid = sqlx::query_scalar!(
+        "INSERT INTO notifications (user_id, actor_id, kind, post_id, comment_id, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, NOW()), COALESCE($6, NOW()))
+        ON CONFLICT (user_id, kind, post_id) WHERE NOT is_read AND kind IN ('like', 'comment')
+        DO UPDATE SET actor_id = EXCLUDED.actor_id, comment_id = EXCLUDED.comment_id, updated_at = EXCLUDED.updated_at
+        RETURNING id",
+        notification.user_id,
+        notification.actor_id,
+        notification.kind.as_str(),
+        notification.post_id,
+        notification.comment_id,
+        created_at
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO notification_actors (notification_id, actor_id, created_at)
+        VALUES ($1, $2, COALESCE($3, NOW())) ON CONFLICT DO NOTHING",
+        notification_id,
+        notification.actor_id,
+        created_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
+
+/// Pushes a stored notification to the recipient's event stream.
+pub async fn publish(events: &EventBus, notification: &NewNotification) {
+    events
+        .publish(
+            Channel::User(notification.user_id),
+            Event::Notification {
+                kind: notification.kind.as_str().to_string(),
+                actor_id: notification.actor_id,
+                post_id: notification.post_id,
+                comment_id: notification.comment_id,
+            },
+        )
+        .await;
+}
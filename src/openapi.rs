@@ -0,0 +1,370 @@
+use crate::{
+    handlers::{
+        account_handlers, auth_handlers, block_handlers, bookmark_handlers, comment_handlers,
+        event_handlers, message_handlers, notification_handlers, post_handlers, profile_handlers,
+        tag_handlers, user_handlers,
+    },
+    model::{
+        CommentResponse, ConversationMember, ConversationResponse, DataExportResponse,
+        MentionEntity, MessageResponse, NotificationResponse, PostResponse, ProfileModel,
+        ProfileResponse, RelationshipResponse, TrendingTagResponse, UserResponse,
+    },
+    response::{JsendResponse, Status},
+    schema::{
+        BookmarkSchema, CommentSchema, CreateConversationSchema, CreatePostSchema,
+        DeleteAccountSchema, LikePostSchema, LoginUserSchema, MessageSchema, RegisterUserSchema,
+    },
+};
+use axum::{response::IntoResponse, Json};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+
+/// Name of the cookie tower-sessions stores the session ID in.
+const SESSION_COOKIE: &str = "id";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Blaze API", description = "Every response body is a JSend envelope."),
+    servers((url = "/api/v1")),
+    paths(
+        auth_handlers::register_handler,
+        auth_handlers::login_handler,
+        auth_handlers::logout_handler,
+        auth_handlers::status_handler,
+        account_handlers::delete_account,
+        account_handlers::create_export,
+        account_handlers::get_export,
+        account_handlers::download_export,
+        user_handlers::get_all_users,
+        profile_handlers::get_profile,
+        profile_handlers::upload_profile_pic,
+        post_handlers::get_all_posts,
+        post_handlers::create_post,
+        post_handlers::get_post,
+        post_handlers::update_post,
+        post_handlers::delete_post,
+        post_handlers::restore_post,
+        post_handlers::react_to_post,
+        comment_handlers::get_comments_handler,
+        comment_handlers::create_comment_handler,
+        bookmark_handlers::create_bookmark,
+        bookmark_handlers::delete_bookmark,
+        bookmark_handlers::get_bookmarks,
+        tag_handlers::get_tag_posts,
+        tag_handlers::get_trending_tags,
+        block_handlers::block_user,
+        block_handlers::unblock_user,
+        block_handlers::mute_user,
+        block_handlers::unmute_user,
+        block_handlers::get_blocks,
+        block_handlers::get_mutes,
+        notification_handlers::get_notifications,
+        notification_handlers::get_unread_count,
+        notification_handlers::mark_read,
+        notification_handlers::mark_all_read,
+        message_handlers::create_conversation,
+        message_handlers::get_conversations,
+        message_handlers::get_unread_count,
+        message_handlers::get_messages,
+        message_handlers::send_message,
+        message_handlers::mark_read,
+        event_handlers::stream_events,
+    ),
+    components(schemas(
+        JsendResponse,
+        Status,
+        RegisterUserSchema,
+        LoginUserSchema,
+        DeleteAccountSchema,
+        CreatePostSchema,
+        CommentSchema,
+        LikePostSchema,
+        BookmarkSchema,
+        CreateConversationSchema,
+        MessageSchema,
+        PostResponse,
+        CommentResponse,
+        MentionEntity,
+        TrendingTagResponse,
+        UserResponse,
+        ProfileModel,
+        ProfileResponse,
+        ConversationMember,
+        ConversationResponse,
+        MessageResponse,
+        NotificationResponse,
+        RelationshipResponse,
+        DataExportResponse,
+    )),
+    modifiers(&SessionCookie),
+)]
+pub struct ApiDoc;
+
+struct SessionCookie;
+
+impl Modify for SessionCookie {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(SESSION_COOKIE))),
+        );
+    }
+}
+
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiDoc;
+    use crate::{
+        config::Config,
+        events::EventBus,
+        health::Readiness,
+        metrics,
+        repo::Repos,
+        route::{create_router, v1},
+        AppState,
+    };
+    use axum::{
+        body::{to_bytes, Body},
+        http::{
+            header::{CONTENT_TYPE, COOKIE, SET_COOKIE},
+            Request, StatusCode,
+        },
+        Router,
+    };
+    use serde_json::{json, Value};
+    use sqlx::postgres::PgPoolOptions;
+    use std::{collections::BTreeSet, sync::Arc, time::Duration};
+    use tower::ServiceExt;
+    use tower_sessions::{MemoryStore, SessionManagerLayer};
+    use utoipa::OpenApi;
+    use uuid::Uuid;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    fn spec_operations() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut operations = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for method in item.as_object().unwrap().keys() {
+                if METHODS.contains(&method.as_str()) {
+                    operations.insert((path.clone(), method.clone()));
+                }
+            }
+        }
+        operations
+    }
+
+    /// Every route the v1 router is built from, with the path in OpenAPI syntax.
+    fn router_operations() -> BTreeSet<(String, String)> {
+        v1::routes()
+            .into_iter()
+            .map(|route| {
+                let path = route
+                    .path
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(param) => format!("{{{}}}", param),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (path, route.method.as_str().to_lowercase())
+            })
+            .collect()
+    }
+
+    /// The path with each parameter replaced by a UUID.
+    fn request_uri(path: &str) -> String {
+        let uri = path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') {
+                    Uuid::nil().to_string()
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("/api/v1{}", uri)
+    }
+
+    /// The application with sessions kept in memory, so protected handlers
+    /// run instead of failing for want of a session.
+    fn test_router() -> Router {
+        let config = Config::load(
+            |key| (key == "DATABASE_URL").then(|| "postgres://127.0.0.1:1/blaze".to_string()),
+            toml::Table::new(),
+        )
+        .unwrap();
+        let state = Arc::new(AppState {
+            // No database runs here; fail fast so those handlers answer 500.
+            db: PgPoolOptions::new()
+                .acquire_timeout(Duration::from_millis(100))
+                .connect_lazy(&config.database_url)
+                .unwrap(),
+            repos: Repos::in_memory(),
+            env: config,
+            events: EventBus::new(None),
+            redis: None,
+            readiness: Readiness::default(),
+            metrics: metrics::detached(),
+            shutdown: tokio::sync::watch::channel(false).1,
+        });
+        create_router(state)
+            .layer(SessionManagerLayer::new(MemoryStore::default()).with_secure(false))
+    }
+
+    async fn send(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        cookie: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Option<String>, Value) {
+        let mut request = Request::builder()
+            .method(method.to_uppercase().as_str())
+            .uri(uri);
+        if let Some(cookie) = cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let cookie = response
+            .headers()
+            .get(SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(str::to_string);
+        // An event stream never ends; its headers are enough.
+        let streaming = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+        if streaming {
+            return (status, cookie, Value::Null);
+        }
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, cookie, body)
+    }
+
+    /// Registers a user on first use and returns a fresh session cookie.
+    async fn log_in(router: &Router) -> String {
+        let credentials = json!({
+            "username": "alice",
+            "email": "alice@example.com",
+            "password": "correct-horse",
+        });
+        send(
+            router,
+            "post",
+            "/api/v1/auth/register",
+            None,
+            Some(credentials.clone()),
+        )
+        .await;
+        let (status, cookie, _) = send(
+            router,
+            "post",
+            "/api/v1/auth/login",
+            None,
+            Some(credentials),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        cookie.unwrap()
+    }
+
+    /// Whether the router itself answered, rather than a handler.
+    fn unrouted(status: StatusCode, body: &Value) -> bool {
+        status == StatusCode::METHOD_NOT_ALLOWED
+            || (status == StatusCode::NOT_FOUND
+                && body["data"]["path"] == "the requested resource was not found")
+    }
+
+    #[test]
+    fn spec_matches_routes() {
+        let spec = spec_operations();
+        let routes = router_operations();
+        let undocumented: Vec<_> = routes.difference(&spec).collect();
+        let unrouted: Vec<_> = spec.difference(&routes).collect();
+        assert!(
+            undocumented.is_empty(),
+            "routes missing from the OpenAPI spec: {:?}",
+            undocumented
+        );
+        assert!(
+            unrouted.is_empty(),
+            "OpenAPI operations without a route: {:?}",
+            unrouted
+        );
+    }
+
+    #[tokio::test]
+    async fn spec_operations_are_routed() {
+        let router = test_router();
+        let mut cookie = log_in(&router).await;
+        for (path, method) in spec_operations() {
+            let uri = request_uri(&path);
+            let (mut status, _, mut body) = send(&router, &method, &uri, Some(&cookie), None).await;
+            // Logging out ends the session the remaining requests use.
+            if status == StatusCode::UNAUTHORIZED {
+                cookie = log_in(&router).await;
+                (status, _, body) = send(&router, &method, &uri, Some(&cookie), None).await;
+            }
+            assert_ne!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+            assert!(
+                !unrouted(status, &body),
+                "{} {} is not routed: {}",
+                method,
+                uri,
+                status
+            );
+        }
+    }
+
+    /// The router answers no method on a documented path that the spec leaves out.
+    #[tokio::test]
+    async fn routes_are_documented() {
+        let router = test_router();
+        let cookie = log_in(&router).await;
+        let spec = spec_operations();
+        let paths: BTreeSet<&String> = spec.iter().map(|(path, _)| path).collect();
+        for path in paths {
+            let uri = request_uri(path);
+            for method in METHODS {
+                if spec.contains(&(path.clone(), method.to_string())) {
+                    continue;
+                }
+                let (status, _, body) = send(&router, method, &uri, Some(&cookie), None).await;
+                assert!(
+                    unrouted(status, &body),
+                    "{} {} is routed but not documented",
+                    method,
+                    uri
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_the_spec() {
+        let (status, _, _) = send(&test_router(), "get", "/openapi.json", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
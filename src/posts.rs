@@ -0,0 +1,26 @@
+use crate::model::UserModel;
+use sqlx::{Pool, Postgres};
+
+const MODERATOR_ROLES: [&str; 2] = ["moderator", "admin"];
+
+/// Whether a user may delete and restore posts written by other users.
+pub fn is_moderator(user: &UserModel) -> bool {
+    MODERATOR_ROLES.contains(&user.role.as_str())
+}
+
+/// Hard-deletes posts that were soft-deleted more than `retention_days` ago.
+/// Their comments, reactions, tags and mentions go with them through
+/// cascading foreign keys. Returns the number of purged posts.
+pub async fn purge_deleted_posts(
+    db: &Pool<Postgres>,
+    retention_days: i32,
+) -> Result<u64, sqlx::Error> {
+    let purged = sqlx::query!(
+        "DELETE FROM posts WHERE deleted_at <= NOW() - make_interval(days => $1)",
+        retention_days
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok(purged)
+}
@@ -0,0 +1,452 @@
+use super::{
+    CommentRepo, CreateUserError, DeletedPost, NewUser, PostRepo, ProfileRepo, ReactionCounts,
+    ReactionRepo, RepoResult, UserRepo,
+};
+use crate::{
+    mentions,
+    model::{
+        CommentResponse, MentionEntity, Mentions, PostResponse, ProfileModel, ProfileResponse,
+        UserModel,
+    },
+    notifications::NewNotification,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::types::Json;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use uuid::Uuid;
+
+struct StoredPost {
+    id: Uuid,
+    user_id: Uuid,
+    title: String,
+    content: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    deleted_by: Option<Uuid>,
+}
+
+struct StoredComment {
+    id: Uuid,
+    user_id: Uuid,
+    post_id: Uuid,
+    content: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct State {
+    users: Vec<UserModel>,
+    profiles: Vec<ProfileModel>,
+    posts: Vec<StoredPost>,
+    comments: Vec<StoredComment>,
+    /// Keyed by post and user; `true` is a like.
+    reactions: HashMap<(Uuid, Uuid), bool>,
+}
+
+/// Keeps everything in process memory. Blocks, tags and notifications are
+/// not modelled: nobody is ever blocked and no notifications are stored.
+#[derive(Clone, Default)]
+pub struct InMemoryStore(Arc<Mutex<State>>);
+
+impl InMemoryStore {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl State {
+    fn user(&self, user_id: Uuid) -> Option<&UserModel> {
+        self.users.iter().find(|user| user.id == Some(user_id))
+    }
+
+    fn username(&self, user_id: Uuid) -> String {
+        self.user(user_id)
+            .map(|user| user.username.clone())
+            .unwrap_or_default()
+    }
+
+    fn profile_image(&self, user_id: Uuid) -> String {
+        self.profiles
+            .iter()
+            .find(|profile| profile.user_id == user_id)
+            .map(|profile| profile.profile_image.clone())
+            .unwrap_or_default()
+    }
+
+    fn live_post(&self, post_id: Uuid) -> Option<&StoredPost> {
+        self.posts
+            .iter()
+            .find(|post| post.id == post_id && post.deleted_at.is_none())
+    }
+
+    fn mentions(&self, content: &str) -> Mentions {
+        let found = mentions::extract_mentions(content)
+            .into_iter()
+            .filter_map(|token| {
+                let user = self
+                    .users
+                    .iter()
+                    .find(|user| user.username == token.username)?;
+                Some(MentionEntity {
+                    user_id: user.id?,
+                    username: user.username.clone(),
+                    start: token.start,
+                    end: token.end,
+                })
+            })
+            .collect();
+        Json(found)
+    }
+
+    fn reaction_counts(&self, post_id: Uuid) -> (i64, i64) {
+        self.reactions
+            .iter()
+            .filter(|((id, _), _)| *id == post_id)
+            .fold((0, 0), |(likes, dislikes), (_, is_like)| {
+                if *is_like {
+                    (likes + 1, dislikes)
+                } else {
+                    (likes, dislikes + 1)
+                }
+            })
+    }
+
+    fn post_response(&self, post: &StoredPost) -> PostResponse {
+        let (likes, dislikes) = self.reaction_counts(post.id);
+        PostResponse {
+            id: post.id,
+            user_id: post.user_id,
+            username: self.username(post.user_id),
+            profile_image: self.profile_image(post.user_id),
+            title: post.title.clone(),
+            content: post.content.clone(),
+            likes,
+            dislikes,
+            comments: self
+                .comments
+                .iter()
+                .filter(|comment| comment.post_id == post.id)
+                .count() as i64,
+            mentions: self.mentions(&post.content),
+            updated_at: post.updated_at,
+            created_at: post.created_at,
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepo for InMemoryStore {
+    async fn find_active(&self, user_id: Uuid) -> RepoResult<Option<UserModel>> {
+        Ok(self
+            .state()
+            .user(user_id)
+            .filter(|user| user.delete_after.is_none())
+            .cloned())
+    }
+
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<UserModel>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.username == username)
+            .cloned())
+    }
+
+    async fn create(&self, user: NewUser<'_>) -> Result<Uuid, CreateUserError> {
+        let mut state = self.state();
+        let email = user.email.to_ascii_lowercase();
+        let username_taken = state.users.iter().any(|u| u.username == user.username);
+        let email_taken = state.users.iter().any(|u| u.email == email);
+        if username_taken || email_taken {
+            return Err(CreateUserError::Taken {
+                username: username_taken,
+                email: email_taken,
+            });
+        }
+
+        let user_id = Uuid::new_v4();
+        let now = Utc::now();
+        state.users.push(UserModel {
+            id: Some(user_id),
+            username: user.username.to_string(),
+            email,
+            password: user.password_hash.to_string(),
+            role: "user".to_string(),
+            created_at: Some(now),
+            updated_at: Some(now),
+            delete_after: None,
+            banned_at: None,
+        });
+        state.profiles.push(ProfileModel {
+            id: Some(Uuid::new_v4()),
+            user_id,
+            profile_image: "default.jpg".to_string(),
+            bio: String::new(),
+            created_at: Some(now),
+            updated_at: Some(now),
+        });
+        Ok(user_id)
+    }
+
+    async fn schedule_deletion(
+        &self,
+        user_id: Uuid,
+        grace_days: i32,
+    ) -> RepoResult<Option<DateTime<Utc>>> {
+        let mut state = self.state();
+        let user = state
+            .users
+            .iter_mut()
+            .find(|user| user.id == Some(user_id))
+            .ok_or(sqlx::Error::RowNotFound)?;
+        user.delete_after = Some(Utc::now() + Duration::days(grace_days.into()));
+        Ok(user.delete_after)
+    }
+
+    async fn cancel_deletion(&self, user_id: Uuid) -> RepoResult<()> {
+        let mut state = self.state();
+        if let Some(user) = state.users.iter_mut().find(|user| user.id == Some(user_id)) {
+            user.delete_after = None;
+        }
+        Ok(())
+    }
+
+    async fn is_blocked_between(&self, _user_id: Uuid, _other_id: Uuid) -> RepoResult<bool> {
+        Ok(false)
+    }
+
+    async fn hidden_from(&self, _viewer_id: Uuid) -> RepoResult<HashSet<Uuid>> {
+        Ok(HashSet::new())
+    }
+}
+
+#[async_trait]
+impl ProfileRepo for InMemoryStore {
+    async fn find_by_user(&self, user_id: Uuid) -> RepoResult<Option<ProfileModel>> {
+        Ok(self
+            .state()
+            .profiles
+            .iter()
+            .find(|profile| profile.user_id == user_id)
+            .cloned())
+    }
+
+    async fn list(&self) -> RepoResult<Vec<ProfileResponse>> {
+        let state = self.state();
+        Ok(state
+            .profiles
+            .iter()
+            .map(|profile| ProfileResponse {
+                profile_id: profile.id,
+                username: state.username(profile.user_id),
+                profile_image: profile.profile_image.clone(),
+            })
+            .collect())
+    }
+
+    async fn set_image(&self, user_id: Uuid, file_name: &str) -> RepoResult<()> {
+        let mut state = self.state();
+        if let Some(profile) = state
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.user_id == user_id)
+        {
+            profile.profile_image = file_name.to_string();
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PostRepo for InMemoryStore {
+    async fn find(
+        &self,
+        post_id: Uuid,
+        _viewer_id: Option<Uuid>,
+    ) -> RepoResult<Option<PostResponse>> {
+        let state = self.state();
+        Ok(state
+            .live_post(post_id)
+            .map(|post| state.post_response(post)))
+    }
+
+    async fn list(&self, _viewer_id: Option<Uuid>) -> RepoResult<Vec<PostResponse>> {
+        let state = self.state();
+        let mut posts: Vec<&StoredPost> = state
+            .posts
+            .iter()
+            .filter(|post| post.deleted_at.is_none())
+            .collect();
+        posts.sort_by_key(|post| Reverse(post.created_at));
+        Ok(posts
+            .into_iter()
+            .map(|post| state.post_response(post))
+            .collect())
+    }
+
+    async fn author(&self, post_id: Uuid) -> RepoResult<Option<Uuid>> {
+        Ok(self.state().live_post(post_id).map(|post| post.user_id))
+    }
+
+    async fn create(
+        &self,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)> {
+        let post_id = Uuid::new_v4();
+        let now = Utc::now();
+        self.state().posts.push(StoredPost {
+            id: post_id,
+            user_id: author_id,
+            title: title.to_string(),
+            content: content.to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            deleted_by: None,
+        });
+        Ok((post_id, Vec::new()))
+    }
+
+    async fn update(
+        &self,
+        post_id: Uuid,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<Option<Vec<NewNotification>>> {
+        let mut state = self.state();
+        let Some(post) = state.posts.iter_mut().find(|post| {
+            post.id == post_id && post.user_id == author_id && post.deleted_at.is_none()
+        }) else {
+            return Ok(None);
+        };
+        // Mentions are read from the content, so only the post changes.
+        post.title = title.to_string();
+        post.content = content.to_string();
+        post.updated_at = Utc::now();
+        Ok(Some(Vec::new()))
+    }
+
+    async fn soft_delete(&self, post_id: Uuid, deleted_by: Uuid) -> RepoResult<bool> {
+        let mut state = self.state();
+        let Some(post) = state
+            .posts
+            .iter_mut()
+            .find(|post| post.id == post_id && post.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+        post.deleted_at = Some(Utc::now());
+        post.deleted_by = Some(deleted_by);
+        Ok(true)
+    }
+
+    async fn find_deleted(
+        &self,
+        post_id: Uuid,
+        retention_days: i32,
+    ) -> RepoResult<Option<DeletedPost>> {
+        let cutoff = Utc::now() - Duration::days(retention_days.into());
+        Ok(self
+            .state()
+            .posts
+            .iter()
+            .find(|post| post.id == post_id && post.deleted_at.is_some_and(|at| at > cutoff))
+            .map(|post| DeletedPost {
+                user_id: post.user_id,
+                deleted_by: post.deleted_by,
+            }))
+    }
+
+    async fn restore(&self, post_id: Uuid) -> RepoResult<()> {
+        let mut state = self.state();
+        if let Some(post) = state.posts.iter_mut().find(|post| post.id == post_id) {
+            post.deleted_at = None;
+            post.deleted_by = None;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommentRepo for InMemoryStore {
+    async fn list(
+        &self,
+        post_id: Uuid,
+        _viewer_id: Option<Uuid>,
+    ) -> RepoResult<Vec<CommentResponse>> {
+        let state = self.state();
+        if state.live_post(post_id).is_none() {
+            return Ok(Vec::new());
+        }
+        let mut comments: Vec<&StoredComment> = state
+            .comments
+            .iter()
+            .filter(|comment| comment.post_id == post_id)
+            .collect();
+        comments.sort_by_key(|comment| Reverse(comment.created_at));
+        Ok(comments
+            .into_iter()
+            .map(|comment| CommentResponse {
+                id: Some(comment.id),
+                username: state.username(comment.user_id),
+                profile_image: state.profile_image(comment.user_id),
+                user_id: comment.user_id,
+                post_id: comment.post_id,
+                content: comment.content.clone(),
+                mentions: state.mentions(&comment.content),
+                created_at: Some(comment.created_at),
+                updated_at: Some(comment.created_at),
+            })
+            .collect())
+    }
+
+    async fn create(
+        &self,
+        post_id: Uuid,
+        _post_author_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)> {
+        let comment_id = Uuid::new_v4();
+        self.state().comments.push(StoredComment {
+            id: comment_id,
+            user_id,
+            post_id,
+            content: content.to_string(),
+            created_at: Utc::now(),
+        });
+        Ok((comment_id, Vec::new()))
+    }
+}
+
+#[async_trait]
+impl ReactionRepo for InMemoryStore {
+    async fn react(
+        &self,
+        post_id: Uuid,
+        _post_author_id: Uuid,
+        user_id: Uuid,
+        is_like: bool,
+    ) -> RepoResult<ReactionCounts> {
+        let mut state = self.state();
+        state.reactions.insert((post_id, user_id), is_like);
+        let (likes, dislikes) = state.reaction_counts(post_id);
+        Ok(ReactionCounts {
+            likes,
+            dislikes,
+            notification: None,
+        })
+    }
+}
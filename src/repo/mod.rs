@@ -0,0 +1,180 @@
+//! Data access for users, profiles, posts, comments and reactions. Handlers
+//! go through these traits instead of querying Postgres directly, so the
+//! in-memory implementations can stand in for the database in tests.
+//! Operations that must be atomic, such as saving a post together with its
+//! tags and mentions, are single methods so each implementation can keep
+//! them in one transaction.
+
+mod memory;
+mod postgres;
+
+use crate::{
+    model::{CommentResponse, PostResponse, ProfileModel, ProfileResponse, UserModel},
+    notifications::NewNotification,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use std::{collections::HashSet, sync::Arc};
+use uuid::Uuid;
+
+pub use memory::InMemoryStore;
+
+pub type RepoResult<T> = Result<T, sqlx::Error>;
+
+pub struct NewUser<'a> {
+    pub username: &'a str,
+    pub email: &'a str,
+    pub password_hash: &'a str,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateUserError {
+    #[error("username or email already exists")]
+    Taken { username: bool, email: bool },
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct DeletedPost {
+    pub user_id: Uuid,
+    pub deleted_by: Option<Uuid>,
+}
+
+pub struct ReactionCounts {
+    pub likes: i64,
+    pub dislikes: i64,
+    /// The like notification for the post author, when one was stored.
+    pub notification: Option<NewNotification>,
+}
+
+#[async_trait]
+pub trait UserRepo: Send + Sync {
+    /// A user who is not scheduled for deletion.
+    async fn find_active(&self, user_id: Uuid) -> RepoResult<Option<UserModel>>;
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<UserModel>>;
+    /// Creates the user along with an empty profile, or neither of them when
+    /// the username or email is already in use.
+    async fn create(&self, user: NewUser<'_>) -> Result<Uuid, CreateUserError>;
+    async fn schedule_deletion(
+        &self,
+        user_id: Uuid,
+        grace_days: i32,
+    ) -> RepoResult<Option<DateTime<Utc>>>;
+    async fn cancel_deletion(&self, user_id: Uuid) -> RepoResult<()>;
+    async fn is_blocked_between(&self, user_id: Uuid, other_id: Uuid) -> RepoResult<bool>;
+    /// The users whose posts and comments are hidden from the viewer.
+    async fn hidden_from(&self, viewer_id: Uuid) -> RepoResult<HashSet<Uuid>>;
+}
+
+#[async_trait]
+pub trait ProfileRepo: Send + Sync {
+    async fn find_by_user(&self, user_id: Uuid) -> RepoResult<Option<ProfileModel>>;
+    async fn list(&self) -> RepoResult<Vec<ProfileResponse>>;
+    async fn set_image(&self, user_id: Uuid, file_name: &str) -> RepoResult<()>;
+}
+
+#[async_trait]
+pub trait PostRepo: Send + Sync {
+    /// A post that is not deleted and not hidden from the viewer by a block.
+    async fn find(
+        &self,
+        post_id: Uuid,
+        viewer_id: Option<Uuid>,
+    ) -> RepoResult<Option<PostResponse>>;
+    /// Every visible post, newest first.
+    async fn list(&self, viewer_id: Option<Uuid>) -> RepoResult<Vec<PostResponse>>;
+    /// The author of a post that is not deleted.
+    async fn author(&self, post_id: Uuid) -> RepoResult<Option<Uuid>>;
+    /// Saves a post with its tags and mentions, returning the notifications
+    /// stored for mentioned users.
+    async fn create(
+        &self,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)>;
+    /// Edits a post of the author that is not deleted, replacing its tags and
+    /// mentions. Returns `None`, changing nothing, when there is no such post.
+    async fn update(
+        &self,
+        post_id: Uuid,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<Option<Vec<NewNotification>>>;
+    /// Deletes a post that is not deleted yet, returning whether there was one.
+    async fn soft_delete(&self, post_id: Uuid, deleted_by: Uuid) -> RepoResult<bool>;
+    /// A post deleted within the last `retention_days`.
+    async fn find_deleted(
+        &self,
+        post_id: Uuid,
+        retention_days: i32,
+    ) -> RepoResult<Option<DeletedPost>>;
+    async fn restore(&self, post_id: Uuid) -> RepoResult<()>;
+}
+
+#[async_trait]
+pub trait CommentRepo: Send + Sync {
+    async fn list(
+        &self,
+        post_id: Uuid,
+        viewer_id: Option<Uuid>,
+    ) -> RepoResult<Vec<CommentResponse>>;
+    /// Saves a comment with its mentions and notifies the post author,
+    /// returning the notifications that were stored.
+    async fn create(
+        &self,
+        post_id: Uuid,
+        post_author_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)>;
+}
+
+#[async_trait]
+pub trait ReactionRepo: Send + Sync {
+    /// Sets the user's reaction to a post, replacing an earlier one, and
+    /// returns the post's updated counts.
+    async fn react(
+        &self,
+        post_id: Uuid,
+        post_author_id: Uuid,
+        user_id: Uuid,
+        is_like: bool,
+    ) -> RepoResult<ReactionCounts>;
+}
+
+#[derive(Clone)]
+pub struct Repos {
+    pub users: Arc<dyn UserRepo>,
+    pub profiles: Arc<dyn ProfileRepo>,
+    pub posts: Arc<dyn PostRepo>,
+    pub comments: Arc<dyn CommentRepo>,
+    pub reactions: Arc<dyn ReactionRepo>,
+}
+
+impl Repos {
+    pub fn postgres(db: Pool<Postgres>) -> Self {
+        Repos {
+            users: Arc::new(postgres::PgUserRepo::new(db.clone())),
+            profiles: Arc::new(postgres::PgProfileRepo::new(db.clone())),
+            posts: Arc::new(postgres::PgPostRepo::new(db.clone())),
+            comments: Arc::new(postgres::PgCommentRepo::new(db.clone())),
+            reactions: Arc::new(postgres::PgReactionRepo::new(db)),
+        }
+    }
+
+    /// Repositories backed by one shared in-memory store. Blocks, tags and
+    /// notifications are not modelled.
+    pub fn in_memory() -> Self {
+        let store = InMemoryStore::default();
+        Repos {
+            users: Arc::new(store.clone()),
+            profiles: Arc::new(store.clone()),
+            posts: Arc::new(store.clone()),
+            comments: Arc::new(store.clone()),
+            reactions: Arc::new(store),
+        }
+    }
+}
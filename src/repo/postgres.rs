@@ -0,0 +1,520 @@
+use super::{
+    CommentRepo, CreateUserError, DeletedPost, NewUser, PostRepo, ProfileRepo, ReactionCounts,
+    ReactionRepo, RepoResult, UserRepo,
+};
+use crate::{
+    blocks,
+    mentions::{self, MentionSource},
+    model::{CommentResponse, Mentions, PostResponse, ProfileModel, ProfileResponse, UserModel},
+    notifications::{self, NewNotification, NotificationKind},
+    tags,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+pub struct PgUserRepo {
+    db: Pool<Postgres>,
+}
+
+impl PgUserRepo {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        PgUserRepo { db }
+    }
+
+    /// Whether the username and the (lowercased) email are in use.
+    async fn taken(&self, username: &str, email: &str) -> RepoResult<(bool, bool)> {
+        let taken = sqlx::query!(
+            r#"SELECT
+                EXISTS (SELECT 1 FROM users WHERE username = $1) AS "username!",
+                EXISTS (SELECT 1 FROM users WHERE email = $2) AS "email!""#,
+            username,
+            email
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok((taken.username, taken.email))
+    }
+}
+
+#[async_trait]
+impl UserRepo for PgUserRepo {
+    async fn find_active(&self, user_id: Uuid) -> RepoResult<Option<UserModel>> {
+        sqlx::query_as!(
+            UserModel,
+            "SELECT * FROM users WHERE id = $1 AND delete_after IS NULL",
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<UserModel>> {
+        sqlx::query_as!(
+            UserModel,
+            "SELECT * FROM users WHERE username = $1",
+            username
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    async fn create(&self, user: NewUser<'_>) -> Result<Uuid, CreateUserError> {
+        let email = user.email.to_ascii_lowercase();
+        let mut tx = self.db.begin().await?;
+
+        let inserted = sqlx::query_scalar!(
+            "INSERT INTO users (username, email, password) VALUES ($1, $2, $3) RETURNING id",
+            user.username,
+            email,
+            user.password_hash
+        )
+        .fetch_one(&mut *tx)
+        .await;
+
+        let user_id: Uuid = match inserted {
+            Ok(user_id) => user_id,
+            // The unique constraints are what keep concurrent registrations
+            // apart; the lookup afterwards only finds every field to report.
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                drop(tx);
+                let constraint = err.constraint().unwrap_or_default();
+                let (username, email) = self.taken(user.username, &email).await?;
+                return Err(CreateUserError::Taken {
+                    username: username || constraint == "users_username_key",
+                    email: email || constraint == "users_email_key",
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        sqlx::query!(
+            "INSERT INTO profiles (user_id, profile_image, bio) VALUES ($1, $2, $3)",
+            user_id,
+            "default.jpg",
+            ""
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(user_id)
+    }
+
+    async fn schedule_deletion(
+        &self,
+        user_id: Uuid,
+        grace_days: i32,
+    ) -> RepoResult<Option<DateTime<Utc>>> {
+        sqlx::query_scalar!(
+            "UPDATE users SET delete_after = NOW() + make_interval(days => $1) WHERE id = $2 RETURNING delete_after",
+            grace_days,
+            user_id
+        )
+        .fetch_one(&self.db)
+        .await
+    }
+
+    async fn cancel_deletion(&self, user_id: Uuid) -> RepoResult<()> {
+        sqlx::query!(
+            "UPDATE users SET delete_after = NULL WHERE id = $1",
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn is_blocked_between(&self, user_id: Uuid, other_id: Uuid) -> RepoResult<bool> {
+        blocks::is_blocked_between(&self.db, user_id, other_id).await
+    }
+
+    async fn hidden_from(&self, viewer_id: Uuid) -> RepoResult<HashSet<Uuid>> {
+        blocks::hidden_from(&self.db, viewer_id).await
+    }
+}
+
+pub struct PgProfileRepo {
+    db: Pool<Postgres>,
+}
+
+impl PgProfileRepo {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        PgProfileRepo { db }
+    }
+}
+
+#[async_trait]
+impl ProfileRepo for PgProfileRepo {
+    async fn find_by_user(&self, user_id: Uuid) -> RepoResult<Option<ProfileModel>> {
+        sqlx::query_as!(
+            ProfileModel,
+            "SELECT id, user_id, profile_image, bio, created_at, updated_at FROM profiles WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    async fn list(&self) -> RepoResult<Vec<ProfileResponse>> {
+        sqlx::query_as!(
+            ProfileResponse,
+            "SELECT profiles.id AS profile_id, profiles.profile_image, username FROM users JOIN profiles on users.id = profiles.user_id"
+        )
+        .fetch_all(&self.db)
+        .await
+    }
+
+    async fn set_image(&self, user_id: Uuid, file_name: &str) -> RepoResult<()> {
+        sqlx::query!(
+            "UPDATE profiles SET profile_image = $1 WHERE user_id = $2",
+            file_name,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PgPostRepo {
+    db: Pool<Postgres>,
+}
+
+impl PgPostRepo {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        PgPostRepo { db }
+    }
+
+    /// The one post with `post_id`, or every post when it is `None`.
+    async fn fetch(
+        &self,
+        post_id: Option<Uuid>,
+        viewer_id: Option<Uuid>,
+    ) -> RepoResult<Vec<PostResponse>> {
+        sqlx::query_as!(
+            PostResponse,
+            r#"SELECT
+                posts.id,
+                users.username,
+                posts.title,
+                posts.content,
+                posts.created_at,
+                posts.updated_at,
+                posts.user_id,
+                profiles.profile_image,
+                posts.like_count AS likes,
+                posts.dislike_count AS dislikes,
+                posts.comment_count AS comments,
+                post_mentions(posts.id) AS "mentions!: Mentions"
+            FROM posts
+            JOIN users ON posts.user_id = users.id
+            JOIN profiles ON profiles.user_id = users.id
+            WHERE ($1::UUID IS NULL OR posts.id = $1)
+                AND posts.deleted_at IS NULL
+                AND NOT is_hidden_from(posts.user_id, $2)
+            ORDER BY posts.created_at DESC"#,
+            post_id,
+            viewer_id
+        )
+        .fetch_all(&self.db)
+        .await
+    }
+}
+
+#[async_trait]
+impl PostRepo for PgPostRepo {
+    async fn find(
+        &self,
+        post_id: Uuid,
+        viewer_id: Option<Uuid>,
+    ) -> RepoResult<Option<PostResponse>> {
+        Ok(self.fetch(Some(post_id), viewer_id).await?.pop())
+    }
+
+    async fn list(&self, viewer_id: Option<Uuid>) -> RepoResult<Vec<PostResponse>> {
+        self.fetch(None, viewer_id).await
+    }
+
+    async fn author(&self, post_id: Uuid) -> RepoResult<Option<Uuid>> {
+        sqlx::query_scalar!(
+            "SELECT user_id FROM posts WHERE id = $1 AND deleted_at IS NULL",
+            post_id
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    async fn create(
+        &self,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)> {
+        let mut tx = self.db.begin().await?;
+
+        let post_id: Uuid = sqlx::query_scalar!(
+            "INSERT INTO posts (user_id,title,content) VALUES ($1,$2,$3) RETURNING id",
+            author_id,
+            title,
+            content
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tags::save_post_tags(&mut tx, post_id, content).await?;
+
+        let mentioned =
+            mentions::save_mentions(&mut tx, author_id, MentionSource::Post { post_id }, content)
+                .await?;
+
+        tx.commit().await?;
+        Ok((post_id, mentioned))
+    }
+
+    async fn update(
+        &self,
+        post_id: Uuid,
+        author_id: Uuid,
+        title: &str,
+        content: &str,
+    ) -> RepoResult<Option<Vec<NewNotification>>> {
+        let mut tx = self.db.begin().await?;
+
+        // The row stays locked until the commit, so concurrent edits of the
+        // same post replace its tags and mentions one after the other.
+        let updated = sqlx::query!(
+            "UPDATE posts SET title = $1, content = $2, updated_at = NOW()
+            WHERE id = $3 AND user_id = $4 AND deleted_at IS NULL",
+            title,
+            content,
+            post_id,
+            author_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        tags::save_post_tags(&mut tx, post_id, content).await?;
+
+        let mentioned =
+            mentions::save_mentions(&mut tx, author_id, MentionSource::Post { post_id }, content)
+                .await?;
+
+        tx.commit().await?;
+        Ok(Some(mentioned))
+    }
+
+    async fn soft_delete(&self, post_id: Uuid, deleted_by: Uuid) -> RepoResult<bool> {
+        let deleted = sqlx::query!(
+            "UPDATE posts SET deleted_at = NOW(), deleted_by = $1
+            WHERE id = $2 AND deleted_at IS NULL",
+            deleted_by,
+            post_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn find_deleted(
+        &self,
+        post_id: Uuid,
+        retention_days: i32,
+    ) -> RepoResult<Option<DeletedPost>> {
+        sqlx::query_as!(
+            DeletedPost,
+            "SELECT user_id, deleted_by FROM posts
+            WHERE id = $1 AND deleted_at > NOW() - make_interval(days => $2)",
+            post_id,
+            retention_days
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    async fn restore(&self, post_id: Uuid) -> RepoResult<()> {
+        sqlx::query!(
+            "UPDATE posts SET deleted_at = NULL, deleted_by = NULL WHERE id = $1",
+            post_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+}
+
+pub struct PgCommentRepo {
+    db: Pool<Postgres>,
+}
+
+impl PgCommentRepo {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        PgCommentRepo { db }
+    }
+}
+
+#[async_trait]
+impl CommentRepo for PgCommentRepo {
+    async fn list(
+        &self,
+        post_id: Uuid,
+        viewer_id: Option<Uuid>,
+    ) -> RepoResult<Vec<CommentResponse>> {
+        sqlx::query_as!(
+            CommentResponse,
+            r#"SELECT
+                comments.id,
+                users.username,
+                comments.user_id,
+                comments.post_id,
+                comments.content,
+                comment_mentions(comments.id) AS "mentions!: Mentions",
+                comments.created_at,
+                comments.updated_at,
+                profiles.profile_image
+            FROM comments
+            JOIN users ON comments.user_id = users.id
+            JOIN profiles ON comments.user_id = profiles.user_id
+            JOIN posts ON comments.post_id = posts.id
+            WHERE comments.post_id = $1 AND posts.deleted_at IS NULL AND NOT is_hidden_from(comments.user_id, $2)
+            ORDER BY comments.created_at DESC"#,
+            post_id,
+            viewer_id
+        )
+        .fetch_all(&self.db)
+        .await
+    }
+
+    async fn create(
+        &self,
+        post_id: Uuid,
+        post_author_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> RepoResult<(Uuid, Vec<NewNotification>)> {
+        let mut tx = self.db.begin().await?;
+
+        let comment_id: Uuid = sqlx::query_scalar!(
+            "INSERT INTO comments (content,user_id,post_id) VALUES ($1,$2,$3) RETURNING id",
+            content,
+            user_id,
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let notification = NewNotification {
+            user_id: post_author_id,
+            actor_id: user_id,
+            kind: NotificationKind::Comment,
+            post_id: Some(post_id),
+            comment_id: Some(comment_id),
+        };
+        let notified = notifications::notify(&mut tx, &notification).await?;
+
+        let mentioned = mentions::save_mentions(
+            &mut tx,
+            user_id,
+            MentionSource::Comment {
+                post_id,
+                comment_id,
+            },
+            content,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        let mut stored = Vec::new();
+        if notified {
+            stored.push(notification);
+        }
+        stored.extend(mentioned);
+        Ok((comment_id, stored))
+    }
+}
+
+pub struct PgReactionRepo {
+    db: Pool<Postgres>,
+}
+
+impl PgReactionRepo {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        PgReactionRepo { db }
+    }
+}
+
+#[async_trait]
+impl ReactionRepo for PgReactionRepo {
+    async fn react(
+        &self,
+        post_id: Uuid,
+        post_author_id: Uuid,
+        user_id: Uuid,
+        is_like: bool,
+    ) -> RepoResult<ReactionCounts> {
+        let mut tx = self.db.begin().await?;
+
+        let existing_reaction = sqlx::query!(
+            "SELECT id, reaction_type FROM reactions WHERE post_id = $1 AND user_id = $2",
+            post_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let previous_reaction = existing_reaction.as_ref().map(|r| r.reaction_type);
+
+        if let Some(reaction) = existing_reaction {
+            // Update the existing reaction
+            sqlx::query!(
+                "UPDATE reactions SET reaction_type = $1 WHERE id = $2",
+                is_like,
+                reaction.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            // Insert a new reaction
+            sqlx::query!(
+                "INSERT INTO reactions (post_id, user_id,  reaction_type) VALUES ($1, $2, $3)",
+                post_id,
+                user_id,
+                is_like
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let notification = NewNotification {
+            user_id: post_author_id,
+            actor_id: user_id,
+            kind: NotificationKind::Like,
+            post_id: Some(post_id),
+            comment_id: None,
+        };
+        let notified = is_like
+            && previous_reaction != Some(true)
+            && notifications::notify(&mut tx, &notification).await?;
+
+        let counts = sqlx::query!(
+            "SELECT like_count AS likes, dislike_count AS dislikes FROM posts WHERE id = $1",
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(ReactionCounts {
+            likes: counts.likes,
+            dislikes: counts.dislikes,
+            notification: notified.then_some(notification),
+        })
+    }
+}
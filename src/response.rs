@@ -1,6 +1,8 @@
+use crate::request_id;
 use axum::async_trait;
 use axum::body::Body;
-use axum::extract::rejection::PathRejection;
+use axum::extract::multipart::MultipartError;
+use axum::extract::rejection::{PathRejection, QueryRejection};
 use axum::extract::FromRequestParts;
 use axum::extract::{rejection::JsonRejection, FromRequest};
 use axum::http::request::Parts;
@@ -12,9 +14,10 @@ use serde_json::{json, Value};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use thiserror::Error;
+use utoipa::ToSchema;
 use validator::ValidationErrors;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub enum Status {
     #[serde(rename = "success")]
     Success,
@@ -24,13 +27,16 @@ pub enum Status {
     Error,
 }
 
-#[derive(Serialize, Deserialize)]
+/// The JSend envelope wrapping every response body.
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct JsendResponse {
     pub status: Status,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl JsendResponse {
@@ -39,6 +45,7 @@ impl JsendResponse {
             status: Status::Success,
             message: None,
             data,
+            request_id: None,
         }
     }
     pub fn error(message: String) -> Self {
@@ -46,6 +53,7 @@ impl JsendResponse {
             status: Status::Error,
             message: Some(message),
             data: None,
+            request_id: None,
         }
     }
     pub fn fail(data: Value) -> Self {
@@ -53,24 +61,47 @@ impl JsendResponse {
             status: Status::Fail,
             message: None,
             data: Some(data),
+            request_id: None,
         }
     }
 }
 
+/// Errors returned by handlers. Client errors are rendered as JSend `fail`
+/// responses carrying per-field messages, server errors as JSend `error`
+/// responses; each variant maps to its own HTTP status code.
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("internal server error")]
     InternalServerError,
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("session error: {0}")]
+    Session(#[from] tower_sessions::session::Error),
+    #[error("invalid multipart data: {0}")]
+    Multipart(#[from] MultipartError),
     #[error("invalid json error")]
     JsonRejection(JsonRejection),
-    #[error("validation error")]
-    ValidationError(#[from] ValidationErrors),
     #[error("invalid path")]
     PathRejection(PathRejection),
-    #[error("jsend fail")]
-    JsendFail(Value),
-    #[error("jsend error")]
-    JsendError(String),
+    #[error("invalid query")]
+    QueryRejection(QueryRejection),
+    #[error("bad request")]
+    BadRequest(Value),
+    #[error("validation error")]
+    Validation(Value),
+    #[error("unauthorized")]
+    Unauthorized(Value),
+    #[error("forbidden")]
+    Forbidden(Value),
+    #[error("not found")]
+    NotFound(Value),
+    #[error("conflict")]
+    Conflict(Value),
+    #[allow(dead_code)]
+    #[error("rate limited")]
+    RateLimited(Value),
 }
 
 //fn serialize_option_value<S>(option: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error>
@@ -83,33 +114,61 @@ pub enum AppError {
 //    }
 //}
 
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::InternalServerError
+            | AppError::Database(_)
+            | AppError::Io(_)
+            | AppError::Session(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Multipart(err) => err.status(),
+            AppError::JsonRejection(rejection) => rejection.status(),
+            AppError::PathRejection(rejection) => rejection.status(),
+            AppError::QueryRejection(rejection) => rejection.status(),
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response<Body> {
-        let (status_code, response) = match self {
-            AppError::InternalServerError => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                JsendResponse::error("internal server error".to_string()),
-            ),
-            AppError::JsonRejection(_) => (
-                StatusCode::BAD_REQUEST,
-                JsendResponse::error("invalid json data".to_string()),
-            ),
-            AppError::ValidationError(err) => (
-                StatusCode::OK,
-                JsendResponse::fail(valiation_error_to_hashmap(err)),
-            ),
-            AppError::PathRejection(_) => (
-                StatusCode::OK,
-                JsendResponse::error("invalid path data".to_string()),
-            ),
-            AppError::JsendError(message) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                JsendResponse::error(message),
-            ),
-            AppError::JsendFail(data) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, JsendResponse::fail(data))
+        let status_code = self.status_code();
+        if status_code.is_server_error() {
+            tracing::error!(error = ?self, "request failed: {}", self);
+        } else {
+            tracing::debug!("request rejected: {}", self);
+        }
+
+        let mut response = match self {
+            AppError::InternalServerError
+            | AppError::Database(_)
+            | AppError::Io(_)
+            | AppError::Session(_) => JsendResponse::error("internal server error".to_string()),
+            AppError::Multipart(err) => rejected(status_code, "file", err.body_text()),
+            AppError::JsonRejection(rejection) => {
+                rejected(status_code, "body", rejection.body_text())
+            }
+            AppError::PathRejection(rejection) => {
+                rejected(status_code, "path", rejection.body_text())
             }
+            AppError::QueryRejection(rejection) => {
+                rejected(status_code, "query", rejection.body_text())
+            }
+            AppError::BadRequest(data)
+            | AppError::Validation(data)
+            | AppError::Unauthorized(data)
+            | AppError::Forbidden(data)
+            | AppError::NotFound(data)
+            | AppError::Conflict(data)
+            | AppError::RateLimited(data) => JsendResponse::fail(data),
         };
+        response.request_id = request_id::current();
 
         Response::builder()
             .status(status_code)
@@ -119,6 +178,21 @@ impl IntoResponse for AppError {
     }
 }
 
+/// A rejected request body, path, query or upload, reported under `key`. The few
+/// rejections that mean the route itself is broken stay server errors.
+fn rejected(status_code: StatusCode, key: &str, reason: String) -> JsendResponse {
+    if status_code.is_server_error() {
+        return JsendResponse::error("internal server error".to_string());
+    }
+    JsendResponse::fail(json!({ key: reason }))
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        Self::Validation(valiation_error_to_hashmap(errors))
+    }
+}
+
 impl From<JsonRejection> for AppError {
     fn from(rejection: JsonRejection) -> Self {
         Self::JsonRejection(rejection)
@@ -155,6 +229,23 @@ where
     }
 }
 
+pub struct AppQuery<T>(pub T);
+#[async_trait]
+impl<S, T> FromRequestParts<S> for AppQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::extract::Query::<T>::from_request_parts(parts, state).await {
+            Ok(value) => Ok(Self(value.0)),
+            Err(rejection) => Err(AppError::QueryRejection(rejection)),
+        }
+    }
+}
+
 fn valiation_error_to_hashmap(err: ValidationErrors) -> Value {
     let mut error_map: HashMap<String, String> = HashMap::new();
 
@@ -164,7 +255,7 @@ fn valiation_error_to_hashmap(err: ValidationErrors) -> Value {
             .map(|e| {
                 e.message
                     .clone()
-                    .unwrap_or_else(|| Cow::Borrowed("invalid value"))
+                    .unwrap_or(Cow::Borrowed("invalid value"))
                     .into_owned()
             })
             .collect();
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Request, State},
+    http::{header::LINK, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// Headers set on deprecated responses, which browsers must be allowed to read.
+pub const DEPRECATION_HEADERS: [HeaderName; 3] = [DEPRECATION, SUNSET, LINK];
+
+/// Describes a deprecated set of routes and where clients should move to.
+#[derive(Clone)]
+pub struct Deprecation {
+    successor: &'static str,
+    sunset: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(successor: &'static str) -> Self {
+        Deprecation {
+            successor,
+            sunset: None,
+        }
+    }
+
+    /// Announces the HTTP date after which the routes will be removed.
+    pub fn sunset(mut self, date: String) -> Self {
+        self.sunset = Some(date);
+        self
+    }
+}
+
+/// Marks responses as deprecated with the `Deprecation` header and links to
+/// the successor version, plus a `Sunset` header once a removal date is set.
+pub async fn deprecated(
+    State(deprecation): State<Deprecation>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!(
+        "<{}>; rel=\"successor-version\"",
+        deprecation.successor
+    )) {
+        headers.insert(LINK, link);
+    }
+    if let Some(sunset) = deprecation
+        .sunset
+        .as_deref()
+        .and_then(|date| HeaderValue::from_str(date).ok())
+    {
+        headers.insert(SUNSET, sunset);
+    }
+    response
+}
@@ -0,0 +1,63 @@
+mod deprecation;
+pub(crate) mod v1;
+
+use crate::{
+    handlers::{error_handlers, health_handlers, metrics_handlers},
+    metrics,
+    openapi::{self, ApiDoc},
+    AppState,
+};
+use axum::{middleware, routing::get, Router};
+use deprecation::{deprecated, Deprecation};
+use std::sync::Arc;
+use utoipa::OpenApi;
+use utoipa_redoc::{Redoc, Servable};
+
+pub use deprecation::DEPRECATION_HEADERS;
+
+/// Mounts every API version under its own prefix. A new version gets its own
+/// module with a `PREFIX` and a `router`, is nested here next to the others,
+/// and the version it replaces is layered with [`deprecated`] pointing at it.
+pub fn create_router(app_state: Arc<AppState>) -> Router {
+    let v1 = v1::router(app_state.clone());
+
+    let mut router = Router::new().nest(v1::PREFIX, v1.clone());
+
+    // Unversioned aliases kept for clients written before versioning.
+    if app_state.env.legacy_routes {
+        let mut deprecation = Deprecation::new(v1::PREFIX);
+        if let Some(date) = &app_state.env.legacy_routes_sunset {
+            deprecation = deprecation.sunset(date.clone());
+        }
+        router = router.merge(v1.layer(middleware::from_fn_with_state(deprecation, deprecated)));
+    }
+
+    router = router
+        .route("/healthz", get(health_handlers::healthz))
+        .route("/readyz", get(health_handlers::readyz))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .merge(Redoc::with_url("/docs", ApiDoc::openapi()))
+        .fallback(error_handlers::fallback_handler)
+        .layer(middleware::from_fn(metrics::track_requests));
+
+    // Scrapes are not counted as requests themselves.
+    if let Some(token) = &app_state.env.metrics_token {
+        router = router.route(
+            "/metrics",
+            get(metrics_handlers::metrics_handler).route_layer(middleware::from_fn_with_state(
+                Arc::<str>::from(token.as_str()),
+                metrics::require_token,
+            )),
+        );
+    }
+
+    router.with_state(app_state)
+}
+
+/// Serves `/metrics` without a token, for a listener that is only reachable
+/// from inside the deployment.
+pub fn create_metrics_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handlers::metrics_handler))
+        .with_state(app_state)
+}
@@ -0,0 +1,271 @@
+use crate::{
+    handlers::{
+        account_handlers, auth_handlers, block_handlers, bookmark_handlers, comment_handlers,
+        event_handlers, message_handlers, notification_handlers, post_handlers, profile_handlers,
+        tag_handlers, user_handlers,
+    },
+    session_auth::{auth, optional_auth},
+    AppState,
+};
+use axum::{
+    handler::Handler,
+    http::Method,
+    middleware,
+    routing::{on, MethodFilter, MethodRouter},
+    Router,
+};
+use std::sync::Arc;
+
+pub const PREFIX: &str = "/api/v1";
+
+/// Who may call a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Requires a session; the user is passed to the handler.
+    User,
+    /// Open to everyone; the user is passed along when there is a session.
+    Viewer,
+}
+
+/// One method on one path. The router is built from these, so the list can
+/// be compared with the OpenAPI document.
+pub struct Route {
+    pub access: Access,
+    pub method: Method,
+    pub path: &'static str,
+    handler: Box<dyn FnOnce(MethodFilter) -> MethodRouter<Arc<AppState>>>,
+}
+
+fn route<H, T>(access: Access, method: Method, path: &'static str, handler: H) -> Route
+where
+    H: Handler<T, Arc<AppState>>,
+    T: 'static,
+{
+    Route {
+        access,
+        method,
+        path,
+        handler: Box::new(move |filter| on(filter, handler)),
+    }
+}
+
+/// Every route of this version.
+pub fn routes() -> Vec<Route> {
+    use Access::{User, Viewer};
+    use Method as M;
+
+    vec![
+        route(User, M::POST, "/posts", post_handlers::create_post),
+        route(
+            User,
+            M::DELETE,
+            "/posts/:post_id",
+            post_handlers::delete_post,
+        ),
+        route(
+            User,
+            M::PATCH,
+            "/posts/:post_id",
+            post_handlers::update_post,
+        ),
+        route(
+            User,
+            M::POST,
+            "/posts/:post_id/react",
+            post_handlers::react_to_post,
+        ),
+        route(
+            User,
+            M::POST,
+            "/posts/:post_id/restore",
+            post_handlers::restore_post,
+        ),
+        route(
+            User,
+            M::POST,
+            "/posts/:post_id/bookmark",
+            bookmark_handlers::create_bookmark,
+        ),
+        route(
+            User,
+            M::DELETE,
+            "/posts/:post_id/bookmark",
+            bookmark_handlers::delete_bookmark,
+        ),
+        route(User, M::DELETE, "/me", account_handlers::delete_account),
+        route(User, M::POST, "/me/export", account_handlers::create_export),
+        route(
+            User,
+            M::GET,
+            "/me/export/:export_id",
+            account_handlers::get_export,
+        ),
+        route(
+            User,
+            M::GET,
+            "/me/export/:export_id/download",
+            account_handlers::download_export,
+        ),
+        route(
+            User,
+            M::GET,
+            "/me/bookmarks",
+            bookmark_handlers::get_bookmarks,
+        ),
+        route(User, M::GET, "/me/blocks", block_handlers::get_blocks),
+        route(User, M::GET, "/me/mutes", block_handlers::get_mutes),
+        route(
+            User,
+            M::POST,
+            "/user/:username/block",
+            block_handlers::block_user,
+        ),
+        route(
+            User,
+            M::DELETE,
+            "/user/:username/block",
+            block_handlers::unblock_user,
+        ),
+        route(
+            User,
+            M::POST,
+            "/user/:username/mute",
+            block_handlers::mute_user,
+        ),
+        route(
+            User,
+            M::DELETE,
+            "/user/:username/mute",
+            block_handlers::unmute_user,
+        ),
+        route(User, M::GET, "/events", event_handlers::stream_events),
+        route(
+            User,
+            M::GET,
+            "/conversations",
+            message_handlers::get_conversations,
+        ),
+        route(
+            User,
+            M::POST,
+            "/conversations",
+            message_handlers::create_conversation,
+        ),
+        route(
+            User,
+            M::GET,
+            "/conversations/unread_count",
+            message_handlers::get_unread_count,
+        ),
+        route(
+            User,
+            M::GET,
+            "/conversations/:conversation_id/messages",
+            message_handlers::get_messages,
+        ),
+        route(
+            User,
+            M::POST,
+            "/conversations/:conversation_id/messages",
+            message_handlers::send_message,
+        ),
+        route(
+            User,
+            M::POST,
+            "/conversations/:conversation_id/read",
+            message_handlers::mark_read,
+        ),
+        route(
+            User,
+            M::GET,
+            "/notifications",
+            notification_handlers::get_notifications,
+        ),
+        route(
+            User,
+            M::GET,
+            "/notifications/unread_count",
+            notification_handlers::get_unread_count,
+        ),
+        route(
+            User,
+            M::POST,
+            "/notifications/read_all",
+            notification_handlers::mark_all_read,
+        ),
+        route(
+            User,
+            M::POST,
+            "/notifications/:notification_id/read",
+            notification_handlers::mark_read,
+        ),
+        route(User, M::POST, "/auth/logout", auth_handlers::logout_handler),
+        route(User, M::POST, "/auth/status", auth_handlers::status_handler),
+        route(
+            User,
+            M::POST,
+            "/posts/:post_id/comments",
+            comment_handlers::create_comment_handler,
+        ),
+        route(
+            User,
+            M::POST,
+            "/profile/upload",
+            profile_handlers::upload_profile_pic,
+        ),
+        route(
+            Viewer,
+            M::GET,
+            "/user/:username",
+            profile_handlers::get_profile,
+        ),
+        route(Viewer, M::GET, "/users", user_handlers::get_all_users),
+        route(Viewer, M::GET, "/posts", post_handlers::get_all_posts),
+        route(
+            Viewer,
+            M::GET,
+            "/tags/trending",
+            tag_handlers::get_trending_tags,
+        ),
+        route(Viewer, M::GET, "/tags/:tag", tag_handlers::get_tag_posts),
+        route(Viewer, M::POST, "/auth/login", auth_handlers::login_handler),
+        route(
+            Viewer,
+            M::POST,
+            "/auth/register",
+            auth_handlers::register_handler,
+        ),
+        route(
+            Viewer,
+            M::GET,
+            "/posts/:post_id/comments",
+            comment_handlers::get_comments_handler,
+        ),
+        route(Viewer, M::GET, "/posts/:post_id", post_handlers::get_post),
+    ]
+}
+
+pub fn router(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let mut protected_routes = Router::new();
+    let mut unprotected_routes = Router::new();
+    for route in routes() {
+        let filter = MethodFilter::try_from(route.method).expect("the method can be routed");
+        let handler = (route.handler)(filter);
+        match route.access {
+            Access::User => protected_routes = protected_routes.route(route.path, handler),
+            Access::Viewer => unprotected_routes = unprotected_routes.route(route.path, handler),
+        }
+    }
+
+    // Apply the middleware layer to protected routes
+    let protected_routes_with_auth =
+        protected_routes.layer(middleware::from_fn_with_state(app_state.clone(), auth));
+    let unprotected_routes_with_viewer = unprotected_routes.layer(middleware::from_fn_with_state(
+        app_state.clone(),
+        optional_auth,
+    ));
+
+    Router::new()
+        .merge(protected_routes_with_auth)
+        .merge(unprotected_routes_with_viewer)
+}
@@ -1,52 +1,163 @@
 use crate::validation::{
-    validate_content_length, validate_email_length, validate_password_length,
-    validate_title_length, validate_username_length,
+    validate_collection_length, validate_content_length, validate_email, validate_login_username,
+    validate_message_length, validate_password_length, validate_title_length, validate_username,
+    ValidationLimits,
 };
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
 pub struct RegisterUserSchema {
     #[serde(default)]
-    #[validate(custom(function = "validate_username_length"))]
+    #[validate(custom(function = "validate_username", use_context))]
     pub username: String,
     #[serde(default)]
-    #[validate(custom(function = "validate_email_length"))]
+    #[validate(custom(function = "validate_email", use_context))]
     pub email: String,
     #[serde(default)]
-    #[validate(custom(function = "validate_password_length"))]
+    #[validate(custom(function = "validate_password_length", use_context))]
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
 pub struct LoginUserSchema {
     #[serde(default)]
-    #[validate(custom(function = "validate_username_length"))]
+    #[validate(custom(function = "validate_login_username", use_context))]
     pub username: String,
     #[serde(default)]
-    #[validate(custom(function = "validate_password_length"))]
+    #[validate(custom(function = "validate_password_length", use_context))]
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
+pub struct DeleteAccountSchema {
+    #[serde(default)]
+    #[validate(custom(function = "validate_password_length", use_context))]
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
 pub struct CreatePostSchema {
     #[serde(default)]
-    #[validate(custom(function = "validate_title_length"))]
+    #[validate(custom(function = "validate_title_length", use_context))]
     pub title: String,
     #[serde(default)]
-    #[validate(custom(function = "validate_content_length"))]
+    #[validate(custom(function = "validate_content_length", use_context))]
     pub content: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
 pub struct CommentSchema {
     #[serde(default)]
-    #[validate(custom(function = "validate_content_length"))]
+    #[validate(custom(function = "validate_content_length", use_context))]
     pub content: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LikePostSchema {
     #[serde(default)]
     pub is_like: bool,
 }
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
+pub struct BookmarkSchema {
+    #[serde(default)]
+    #[validate(custom(function = "validate_collection_length", use_context))]
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BookmarkFilterSchema {
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
+pub struct CreateConversationSchema {
+    #[serde(default)]
+    #[validate(length(min = 1, max = 9, message = "a conversation has 1 to 9 other members"))]
+    pub usernames: Vec<String>,
+    #[serde(default)]
+    #[validate(custom(function = "validate_title_length", use_context))]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(context = ValidationLimits)]
+pub struct MessageSchema {
+    #[serde(default)]
+    #[validate(custom(function = "validate_message_length", use_context))]
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventStreamSchema {
+    #[serde(default)]
+    pub posts: String,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NotificationFilterSchema {
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrendingTagsSchema {
+    #[serde(default = "default_trending_hours")]
+    #[validate(range(min = 1, max = 720, message = "hours must be between 1 and 720"))]
+    pub hours: i32,
+    #[serde(default = "default_trending_limit")]
+    #[validate(range(min = 1, max = 50, message = "limit must be between 1 and 50"))]
+    pub limit: i64,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationSchema {
+    #[serde(default = "default_page")]
+    // Bounded so the offset cannot overflow.
+    #[validate(range(min = 1, max = 10000, message = "page must be between 1 and 10000"))]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    #[validate(range(min = 1, max = 100, message = "per_page must be between 1 and 100"))]
+    pub per_page: i64,
+}
+
+impl PaginationSchema {
+    pub fn limit(&self) -> i64 {
+        self.per_page
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.per_page
+    }
+}
+
+fn default_page() -> i64 {
+    1
+}
+
+fn default_per_page() -> i64 {
+    20
+}
+
+fn default_trending_hours() -> i32 {
+    24
+}
+
+fn default_trending_limit() -> i64 {
+    10
+}
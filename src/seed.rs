@@ -0,0 +1,381 @@
+use crate::{
+    accounts, mentions,
+    mentions::MentionSource,
+    notifications::{self, NewNotification, NotificationKind},
+    tags,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sqlx::{PgConnection, Pool, Postgres};
+use thiserror::Error;
+use uuid::{Builder, Uuid};
+
+const FIRST_NAMES: &[&str] = &[
+    "ada", "alan", "amara", "bruno", "chen", "dana", "elena", "farah", "grace", "hugo", "ines",
+    "jonas", "kemi", "lena", "marco", "nadia", "omar", "priya", "quinn", "rosa", "sven", "tariq",
+    "uma", "vera", "wei", "yusuf", "zoe",
+];
+const LAST_NAMES: &[&str] = &[
+    "alvarez", "berg", "costa", "dubois", "eriksen", "fischer", "garcia", "haddad", "ito",
+    "jensen", "kowalski", "lopez", "moreau", "novak", "okafor", "petrov", "rossi", "silva",
+    "tanaka", "weber",
+];
+const TOPICS: &[&str] = &[
+    "rust",
+    "coffee",
+    "hiking",
+    "music",
+    "photography",
+    "cooking",
+    "gaming",
+    "books",
+    "travel",
+    "gardening",
+    "design",
+    "running",
+];
+const TITLE_OPENERS: &[&str] = &[
+    "Thoughts on",
+    "Why I love",
+    "A week of",
+    "Getting started with",
+    "Unpopular opinion about",
+    "Notes on",
+    "What I learned from",
+    "Weekend",
+];
+const SENTENCES: &[&str] = &[
+    "I did not expect it to be this much fun.",
+    "Still figuring things out, but it is getting easier.",
+    "Would love to hear how everyone else approaches this.",
+    "Tried something new today and it mostly worked.",
+    "Honestly the hardest part was getting started.",
+    "Here is a quick summary of what went well and what did not.",
+    "It took three attempts but the result was worth it.",
+    "Does anyone have recommendations for where to go next?",
+    "Small steps every day add up faster than you think.",
+    "Sharing this in case it helps someone else.",
+];
+const REPLIES: &[&str] = &[
+    "Totally agree with this.",
+    "Great point, I had not thought of it that way.",
+    "Not sure about that, it did not work for me.",
+    "Thanks for sharing!",
+    "Same here, happened to me last week.",
+    "Could you explain a bit more?",
+    "This is exactly what I needed today.",
+    "Love it, keep them coming.",
+];
+const BIOS: &[&str] = &[
+    "Building things on weekends.",
+    "Coffee first.",
+    "Trying to read more books this year.",
+    "Usually outside.",
+    "Here for the good conversations.",
+    "",
+];
+
+/// How much data to generate. Each post gets between zero and twice the
+/// average number of comments and reactions.
+pub struct SeedOptions {
+    pub seed: u64,
+    pub users: usize,
+    pub posts: usize,
+    pub comments_per_post: usize,
+    pub reactions_per_post: usize,
+    pub password: String,
+}
+
+#[derive(Debug, Default)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub posts: usize,
+    pub comments: usize,
+    pub reactions: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SeedError {
+    #[error("failed to hash the password: {0}")]
+    Hash(argon2::password_hash::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+struct SeedUser {
+    id: Uuid,
+    username: String,
+}
+
+/// Fills the database with fake users, profiles, posts, comments and
+/// reactions. The same seed always produces the same users, posts and
+/// comments, ids included, and the same reactions, tags, mentions and
+/// notifications, though the database picks the ids of the last three.
+/// Timestamps are not reproducible: they are spread over the days before the
+/// run, keeping their spacing but moving with it, and reactions and tags are
+/// stamped with the time of the run itself.
+/// Everything is inserted in one transaction, so a failed run leaves nothing
+/// behind.
+pub async fn seed(db: &Pool<Postgres>, options: &SeedOptions) -> Result<SeedSummary, SeedError> {
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let now = Utc::now();
+    let mut summary = SeedSummary::default();
+
+    // Every user shares the hash; hashing thousands of passwords with Argon2
+    // would take minutes.
+    let password = accounts::hash_password(&options.password).map_err(SeedError::Hash)?;
+
+    let mut tx = db.begin().await?;
+
+    let users: Vec<SeedUser> = (0..options.users)
+        .map(|i| SeedUser {
+            id: random_uuid(&mut rng),
+            username: format!(
+                "{}_{}{}",
+                FIRST_NAMES.choose(&mut rng).unwrap(),
+                LAST_NAMES.choose(&mut rng).unwrap(),
+                i
+            ),
+        })
+        .collect();
+    insert_users(&mut tx, &mut rng, &users, &password, now).await?;
+    summary.users = users.len();
+
+    if users.is_empty() {
+        tx.commit().await?;
+        return Ok(summary);
+    }
+
+    for _ in 0..options.posts {
+        let author = users.choose(&mut rng).unwrap();
+        let created_at = now - Duration::minutes(rng.gen_range(0..30 * 24 * 60));
+        let post_id = random_uuid(&mut rng);
+        let topic = TOPICS.choose(&mut rng).unwrap();
+        let title = format!("{} {}", TITLE_OPENERS.choose(&mut rng).unwrap(), topic);
+        let content = post_content(&mut rng, &users, author, topic);
+
+        sqlx::query!(
+            "INSERT INTO posts (id, user_id, title, content, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)",
+            post_id,
+            author.id,
+            title,
+            content,
+            created_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        tags::save_post_tags(&mut tx, post_id, &content).await?;
+        mentions::save_mentions_at(
+            &mut tx,
+            author.id,
+            MentionSource::Post { post_id },
+            &content,
+            created_at,
+        )
+        .await?;
+        summary.posts += 1;
+
+        summary.comments += insert_comments(
+            &mut tx,
+            &mut rng,
+            &users,
+            author,
+            post_id,
+            created_at,
+            now,
+            options.comments_per_post,
+        )
+        .await?;
+
+        summary.reactions += insert_reactions(
+            &mut tx,
+            &mut rng,
+            &users,
+            post_id,
+            options.reactions_per_post,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(summary)
+}
+
+async fn insert_users(
+    conn: &mut PgConnection,
+    rng: &mut ChaCha8Rng,
+    users: &[SeedUser],
+    password: &str,
+    now: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = users.iter().map(|user| user.id).collect();
+    let usernames: Vec<String> = users.iter().map(|user| user.username.clone()).collect();
+    let emails: Vec<String> = users
+        .iter()
+        .map(|user| format!("{}@example.com", user.username))
+        .collect();
+    let created_at: Vec<DateTime<Utc>> = users
+        .iter()
+        .map(|_| now - Duration::days(rng.gen_range(30..365)))
+        .collect();
+    let bios: Vec<String> = users
+        .iter()
+        .map(|_| BIOS.choose(rng).unwrap().to_string())
+        .collect();
+
+    sqlx::query!(
+        "INSERT INTO users (id, username, email, password, created_at, updated_at)
+        SELECT id, username, email, $4, created_at, created_at
+        FROM UNNEST($1::UUID[], $2::VARCHAR[], $3::VARCHAR[], $5::TIMESTAMPTZ[])
+            AS seeded(id, username, email, created_at)",
+        &ids,
+        &usernames,
+        &emails,
+        password,
+        &created_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO profiles (user_id, profile_image, bio, created_at, updated_at)
+        SELECT user_id, 'default.jpg', bio, created_at, created_at
+        FROM UNNEST($1::UUID[], $2::TEXT[], $3::TIMESTAMPTZ[]) AS seeded(user_id, bio, created_at)",
+        &ids,
+        &bios,
+        &created_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Comments are flat, as the schema has no threading: a reply is simply a
+/// later comment that mentions the author of the one before it, the way users
+/// answer each other in the app. Notifications and mentions are dated with
+/// the comment rather than the time of seeding.
+#[allow(clippy::too_many_arguments)]
+async fn insert_comments(
+    conn: &mut PgConnection,
+    rng: &mut ChaCha8Rng,
+    users: &[SeedUser],
+    post_author: &SeedUser,
+    post_id: Uuid,
+    post_created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    average: usize,
+) -> Result<usize, sqlx::Error> {
+    let count = rng.gen_range(0..=average * 2);
+    let mut created_at = post_created_at;
+    let mut previous: Option<&SeedUser> = None;
+
+    for _ in 0..count {
+        let author = users.choose(rng).unwrap();
+        let reply = REPLIES.choose(rng).unwrap();
+        let content = match previous {
+            Some(previous) if previous.id != author.id && rng.gen_bool(0.6) => {
+                format!("@{} {}", previous.username, reply)
+            }
+            _ => reply.to_string(),
+        };
+        created_at = (created_at + Duration::minutes(rng.gen_range(1..180))).min(now);
+        let comment_id = random_uuid(rng);
+
+        sqlx::query!(
+            "INSERT INTO comments (id, user_id, post_id, content, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)",
+            comment_id,
+            author.id,
+            post_id,
+            content,
+            created_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        notifications::notify_at(
+            conn,
+            &NewNotification {
+                user_id: post_author.id,
+                actor_id: author.id,
+                kind: NotificationKind::Comment,
+                post_id: Some(post_id),
+                comment_id: Some(comment_id),
+            },
+            created_at,
+        )
+        .await?;
+        mentions::save_mentions_at(
+            conn,
+            author.id,
+            MentionSource::Comment {
+                post_id,
+                comment_id,
+            },
+            &content,
+            created_at,
+        )
+        .await?;
+
+        previous = Some(author);
+    }
+    Ok(count)
+}
+
+/// Each reaction comes from a different user, and roughly four in five are
+/// likes.
+async fn insert_reactions(
+    conn: &mut PgConnection,
+    rng: &mut ChaCha8Rng,
+    users: &[SeedUser],
+    post_id: Uuid,
+    average: usize,
+) -> Result<usize, sqlx::Error> {
+    let count = rng.gen_range(0..=average * 2).min(users.len());
+    let user_ids: Vec<Uuid> = users
+        .choose_multiple(rng, count)
+        .map(|user| user.id)
+        .collect();
+    let likes: Vec<bool> = user_ids.iter().map(|_| rng.gen_bool(0.8)).collect();
+
+    sqlx::query!(
+        "INSERT INTO reactions (user_id, post_id, reaction_type)
+        SELECT user_id, $2, reaction_type FROM UNNEST($1::UUID[], $3::BOOLEAN[])
+            AS seeded(user_id, reaction_type)",
+        &user_ids,
+        post_id,
+        &likes
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(count)
+}
+
+fn post_content(
+    rng: &mut ChaCha8Rng,
+    users: &[SeedUser],
+    author: &SeedUser,
+    topic: &str,
+) -> String {
+    let count = rng.gen_range(1..=3);
+    let mut sentences: Vec<String> = SENTENCES
+        .choose_multiple(rng, count)
+        .map(|sentence| sentence.to_string())
+        .collect();
+    if rng.gen_bool(0.3) {
+        let friend = users.choose(rng).unwrap();
+        if friend.id != author.id {
+            sentences.push(format!("cc @{}", friend.username));
+        }
+    }
+    sentences.push(format!("#{}", topic));
+    sentences.join(" ")
+}
+
+fn random_uuid(rng: &mut ChaCha8Rng) -> Uuid {
+    Builder::from_random_bytes(rng.gen()).into_uuid()
+}
@@ -1,4 +1,4 @@
-use crate::{model::UserModel, response::AppError};
+use crate::response::AppError;
 use crate::AppState;
 use axum::{
     body::Body,
@@ -17,22 +17,49 @@ pub async fn auth(
     mut req: Request<Body>,
     next: Next,
 ) -> Result<impl IntoResponse, AppError> {
-    if let Some(user_id) = session
-        .get::<Uuid>("user_id")
-        .await
-        .map_err(|_| AppError::InternalServerError)?
-    {
-        let user = sqlx::query_as!(UserModel, "SELECT * FROM users WHERE id = $1", user_id)
-            .fetch_optional(&data.db)
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+    if let Some(user_id) = session.get::<Uuid>("user_id").await? {
+        let user = data.repos.users.find_active(user_id).await?;
 
-        let user =
-            user.ok_or_else(|| AppError::JsendFail(json!({"authentication".to_string() : "user is not authenticated".to_string()})))?;
+        let user = user.ok_or_else(|| {
+            AppError::Unauthorized(
+                json!({"authentication".to_string() : "user is not authenticated".to_string()}),
+            )
+        })?;
+
+        if user.banned_at.is_some() {
+            return Err(AppError::Forbidden(
+                json!({"account" : "account is banned"}),
+            ));
+        }
 
         req.extensions_mut().insert(user);
         Ok(next.run(req).await)
     } else {
-        Err(AppError::JsendFail(json!( {"authentication".to_string() : "user is not authenticated".to_string()} )))
+        Err(AppError::Unauthorized(
+            json!( {"authentication".to_string() : "user is not authenticated".to_string()} ),
+        ))
+    }
+}
+
+/// Like [`auth`], but lets anonymous requests through. The user is attached to
+/// the request only when the session belongs to an existing user.
+pub async fn optional_auth(
+    session: Session,
+    State(data): State<Arc<AppState>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(user_id) = session.get::<Uuid>("user_id").await? {
+        let user = data
+            .repos
+            .users
+            .find_active(user_id)
+            .await?
+            .filter(|user| user.banned_at.is_none());
+
+        if let Some(user) = user {
+            req.extensions_mut().insert(user);
+        }
     }
+    Ok(next.run(req).await)
 }
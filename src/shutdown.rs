@@ -0,0 +1,64 @@
+use crate::health::Readiness;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Resolves when the process receives SIGINT or SIGTERM.
+async fn signal() {
+    let interrupt = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("failed to listen for SIGINT: {:?}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("failed to listen for SIGTERM: {:?}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+/// Waits for a shutdown signal in the background. Once it arrives the server
+/// is marked as not ready, and after `readiness_delay` every clone of the
+/// returned receiver is woken. The delay gives load balancers time to see the
+/// failing readiness probe while the listener still accepts connections.
+pub fn listen(readiness: Readiness, readiness_delay: Duration) -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(false);
+    tokio::spawn(async move {
+        signal().await;
+        readiness.start_shutdown();
+        if !readiness_delay.is_zero() {
+            println!(
+                "✅ Shutdown requested, reporting not ready for {:?}",
+                readiness_delay
+            );
+            tokio::time::sleep(readiness_delay).await;
+        }
+        println!("✅ Shutting down, draining in-flight requests");
+        let _ = sender.send(true);
+    });
+    receiver
+}
+
+/// Resolves once shutdown has been requested. If the sender is dropped
+/// without requesting it, as in tests, it never resolves.
+pub async fn requested(mut receiver: watch::Receiver<bool>) {
+    if receiver.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,149 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/// Extracts the distinct, lowercased `#hashtags` from a piece of text in the
+/// order they first appear. A hashtag starts with `#` at the beginning of the
+/// text or after a non-word character and runs over letters, digits and `_`.
+pub fn extract_hashtags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '#' && !prev.is_some_and(is_tag_char) {
+            let mut tag = String::new();
+            while let Some(&next) = chars.peek() {
+                if !is_tag_char(next) {
+                    break;
+                }
+                tag.push(next);
+                chars.next();
+            }
+            prev = tag.chars().last().or(Some(c));
+
+            if let Some(tag) = normalize_tag(&tag) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            continue;
+        }
+        prev = Some(c);
+    }
+    tags
+}
+
+/// Lowercases a tag name, returning `None` if it is empty, too long or
+/// contains characters that cannot appear in a hashtag.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.strip_prefix('#').unwrap_or(tag);
+    if !tag.chars().all(is_tag_char) {
+        return None;
+    }
+    // Measured after lowercasing, which can add code points: 'İ' becomes
+    // 'i' followed by a combining dot.
+    let tag = tag.to_lowercase();
+    let len = tag.chars().count();
+    if len == 0 || len > MAX_TAG_LENGTH {
+        return None;
+    }
+    Some(tag)
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Replaces the tags attached to a post with the hashtags found in `content`.
+pub async fn save_post_tags(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    let tags = extract_hashtags(content);
+
+    sqlx::query!("DELETE FROM post_tags WHERE post_id = $1", post_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO tags (name) SELECT * FROM UNNEST($1::VARCHAR[]) ON CONFLICT (name) DO NOTHING",
+        &tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO post_tags (post_id, tag_id) SELECT $1, id FROM tags WHERE name = ANY($2)",
+        post_id,
+        &tags
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_hashtags, normalize_tag, MAX_TAG_LENGTH};
+
+    #[test]
+    fn finds_distinct_lowercased_tags_in_order() {
+        assert_eq!(
+            extract_hashtags("#Rust and #rust, then #async_io #RUST"),
+            ["rust", "async_io"]
+        );
+        assert!(extract_hashtags("no tags here, # or #").is_empty());
+    }
+
+    #[test]
+    fn ignores_fragments_in_urls() {
+        assert_eq!(
+            extract_hashtags("see https://example.com/docs#install or c#sharp #real"),
+            ["real"]
+        );
+    }
+
+    #[test]
+    fn a_doubled_hash_starts_a_tag_at_the_second() {
+        assert_eq!(extract_hashtags("##x"), ["x"]);
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_a_tag() {
+        assert_eq!(
+            extract_hashtags("#one. #two! (#three) #four's"),
+            ["one", "two", "three", "four"]
+        );
+    }
+
+    #[test]
+    fn keeps_non_ascii_letters() {
+        assert_eq!(
+            extract_hashtags("#Café #東京 #İstanbul"),
+            ["café", "東京", "i\u{307}stanbul"]
+        );
+    }
+
+    #[test]
+    fn measures_length_after_lowercasing() {
+        let ascii = "A".repeat(MAX_TAG_LENGTH);
+        assert_eq!(normalize_tag(&ascii), Some(ascii.to_lowercase()));
+        assert_eq!(normalize_tag(&"a".repeat(MAX_TAG_LENGTH + 1)), None);
+        // Each 'İ' lowercases to two code points.
+        assert_eq!(
+            normalize_tag(&"İ".repeat(MAX_TAG_LENGTH / 2)).map(|tag| tag.chars().count()),
+            Some(MAX_TAG_LENGTH)
+        );
+        assert_eq!(normalize_tag(&"İ".repeat(MAX_TAG_LENGTH / 2 + 1)), None);
+        assert_eq!(normalize_tag("#"), None);
+        assert_eq!(normalize_tag("not-a-tag"), None);
+    }
+}
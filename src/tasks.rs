@@ -0,0 +1,47 @@
+use crate::{accounts, exports, posts};
+use metrics_exporter_prometheus::PrometheusHandle;
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const METRICS_UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Periodically removes data whose retention period has passed.
+pub fn spawn_purge_task(db: Pool<Postgres>, post_retention_days: i32) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match accounts::purge_deleted_accounts(&db).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("purged {} deleted accounts", purged),
+                Err(err) => tracing::error!("failed to purge deleted accounts: {:?}", err),
+            }
+
+            match posts::purge_deleted_posts(&db, post_retention_days).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("purged {} deleted posts", purged),
+                Err(err) => tracing::error!("failed to purge deleted posts: {:?}", err),
+            }
+
+            match exports::purge_expired_exports(&db).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("purged {} expired data exports", purged),
+                Err(err) => tracing::error!("failed to purge data exports: {:?}", err),
+            }
+        }
+    })
+}
+
+/// Drains recorded histogram samples so they do not pile up between scrapes.
+pub fn spawn_metrics_upkeep(metrics: PrometheusHandle) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(METRICS_UPKEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            metrics.run_upkeep();
+        }
+    })
+}
@@ -1,96 +1,310 @@
 use std::{borrow::Cow, collections::HashMap};
-use validator::ValidationError;
+use unicode_segmentation::UnicodeSegmentation;
+use validator::{ValidateEmail, ValidationError};
 
-pub fn validate_username_length(username: &str) -> Result<(), ValidationError> {
-    let len = username.len();
+/// Names that could pass for the site or its staff, compared case-insensitively.
+const RESERVED_USERNAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "api",
+    "everyone",
+    "help",
+    "me",
+    "moderator",
+    "null",
+    "root",
+    "settings",
+    "staff",
+    "support",
+    "system",
+];
+
+/// Bounds on the length of a value, in graphemes, so an accented letter or
+/// an emoji counts as one character however many code points it takes.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub min: usize,
+    pub max: usize,
+    /// Size of the VARCHAR column the value is stored in. Postgres counts it
+    /// in code points, which can exceed the number of graphemes.
+    pub column: Option<usize>,
+}
+
+impl Limit {
+    const fn new(min: usize, max: usize, column: Option<usize>) -> Self {
+        Limit { min, max, column }
+    }
+}
+
+/// The length limits applied to request bodies, configurable through
+/// `<FIELD>_MIN_LENGTH` and `<FIELD>_MAX_LENGTH`.
+#[derive(Debug, Clone)]
+pub struct ValidationLimits {
+    pub username: Limit,
+    pub email: Limit,
+    pub password: Limit,
+    pub title: Limit,
+    pub content: Limit,
+    pub collection: Limit,
+    pub message: Limit,
+}
+
+impl Default for ValidationLimits {
+    fn default() -> Self {
+        ValidationLimits {
+            username: Limit::new(3, 20, Some(100)),
+            email: Limit::new(3, 255, Some(255)),
+            password: Limit::new(8, 40, None),
+            title: Limit::new(1, 50, Some(50)),
+            content: Limit::new(1, 400, Some(400)),
+            collection: Limit::new(1, 50, Some(50)),
+            message: Limit::new(1, 1000, Some(1000)),
+        }
+    }
+}
+
+/// The full policy for new usernames: length, characters and reserved names.
+/// Logging in checks less, see [`validate_login_username`].
+pub fn validate_username(username: &str, limits: &ValidationLimits) -> Result<(), ValidationError> {
+    validate_username_length(username, limits)?;
+
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        return Err(error(
+            "username may only contain letters, digits, '_', '.' and '-'",
+        ));
+    }
+    // Mentions drop a trailing '.' or '-' as punctuation.
+    let alphanumeric = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    if !alphanumeric(username.chars().next()) || !alphanumeric(username.chars().last()) {
+        return Err(error("username must start and end with a letter or digit"));
+    }
+    if RESERVED_USERNAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(username))
+    {
+        return Err(error("username is reserved"));
+    }
+    Ok(())
+}
+
+pub fn validate_username_length(
+    username: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
+    validate_length(
+        username,
+        limits.username,
+        "username too short",
+        "username too long",
+        "username cannot be empty",
+    )
+}
+
+/// Logging in only requires a name that could be stored, so accounts created
+/// before the policy or under looser limits keep working.
+pub fn validate_login_username(
+    username: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
+    let stored = limits.username.column.unwrap_or(limits.username.max);
     validate_length(
-        len,
-        1,
-        20,
+        username,
+        Limit::new(1, stored, limits.username.column),
         "username too short",
         "username too long",
         "username cannot be empty",
     )
 }
 
-pub fn validate_email_length(username: &str) -> Result<(), ValidationError> {
-    let len = username.len();
+pub fn validate_email(email: &str, limits: &ValidationLimits) -> Result<(), ValidationError> {
     validate_length(
-        len,
-        1,
-        20,
+        email,
+        limits.email,
         "email too short",
         "email too long",
         "email cannot be empty",
-    )
+    )?;
+    if !email.validate_email() {
+        return Err(error("email is not a valid address"));
+    }
+    Ok(())
 }
 
-pub fn validate_password_length(username: &str) -> Result<(), ValidationError> {
-    let len = username.len();
+pub fn validate_password_length(
+    password: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
     validate_length(
-        len,
-        8,
-        40,
+        password,
+        limits.password,
         "password too short",
         "password too long",
         "password cannot be empty",
     )
 }
 
-pub fn validate_title_length(username: &str) -> Result<(), ValidationError> {
-    let len = username.len();
+pub fn validate_title_length(
+    title: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
     validate_length(
-        len,
-        1,
-        20,
+        title,
+        limits.title,
         "title too short",
         "title too long",
         "title cannot be empty",
     )
 }
 
-pub fn validate_content_length(username: &str) -> Result<(), ValidationError> {
-    let len = username.len();
+pub fn validate_content_length(
+    content: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
     validate_length(
-        len,
-        1,
-        225,
+        content,
+        limits.content,
         "content too short",
         "content too long",
         "content cannot be empty",
     )
 }
 
+pub fn validate_collection_length(
+    collection: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
+    validate_length(
+        collection,
+        limits.collection,
+        "collection too short",
+        "collection too long",
+        "collection cannot be empty",
+    )
+}
+
+pub fn validate_message_length(
+    message: &str,
+    limits: &ValidationLimits,
+) -> Result<(), ValidationError> {
+    validate_length(
+        message,
+        limits.message,
+        "message too short",
+        "message too long",
+        "message cannot be empty",
+    )
+}
+
 fn validate_length(
-    len: usize,
-    min: usize,
-    max: usize,
+    value: &str,
+    limit: Limit,
     min_err: &'static str,
     max_err: &'static str,
     empty_err: &'static str,
 ) -> Result<(), ValidationError> {
-    if len == 0 || len == 1 {
-        let error = ValidationError {
-            code: Cow::Borrowed(empty_err),
-            message: Some(Cow::Borrowed(empty_err)),
-            params: HashMap::new(),
-        };
-        Err(error)
-    } else if len < min {
-        let error = ValidationError {
-            code: Cow::Borrowed(min_err),
-            message: Some(Cow::Borrowed(min_err)),
-            params: HashMap::new(),
-        };
-        Err(error)
-    } else if len > max {
-        let error = ValidationError {
-            code: Cow::Borrowed(max_err),
-            message: Some(Cow::Borrowed(max_err)),
-            params: HashMap::new(),
-        };
-        Err(error)
+    let len = value.graphemes(true).count();
+    let overflows_column = limit
+        .column
+        .is_some_and(|column| value.chars().count() > column);
+
+    if len == 0 {
+        Err(error(empty_err))
+    } else if len < limit.min {
+        Err(error(min_err))
+    } else if len > limit.max || overflows_column {
+        Err(error(max_err))
     } else {
         Ok(())
     }
 }
+
+fn error(message: &'static str) -> ValidationError {
+    ValidationError {
+        code: Cow::Borrowed(message),
+        message: Some(Cow::Borrowed(message)),
+        params: HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_length, validate_username, Limit, ValidationLimits};
+
+    /// The error code, if any.
+    fn length(value: &str, limit: Limit) -> Option<String> {
+        validate_length(value, limit, "short", "long", "empty")
+            .err()
+            .map(|err| err.code.to_string())
+    }
+
+    fn username(name: &str) -> Option<String> {
+        validate_username(name, &ValidationLimits::default())
+            .err()
+            .map(|err| err.code.to_string())
+    }
+
+    #[test]
+    fn combining_sequences_count_once() {
+        let limit = Limit::new(2, 3, None);
+        // "e" followed by a combining acute accent.
+        assert_eq!(length("e\u{301}", limit).as_deref(), Some("short"));
+        assert_eq!(length("e\u{301}e\u{301}e\u{301}", limit), None);
+        assert_eq!(
+            length("e\u{301}e\u{301}e\u{301}e", limit).as_deref(),
+            Some("long")
+        );
+        assert_eq!(length("", limit).as_deref(), Some("empty"));
+    }
+
+    #[test]
+    fn emoji_sequences_count_once_but_must_fit_the_column() {
+        // Man, woman and girl joined by zero-width joiners: five code points.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(length(family, Limit::new(1, 1, None)), None);
+        assert_eq!(length(family, Limit::new(1, 1, Some(5))), None);
+        assert_eq!(
+            length(family, Limit::new(1, 1, Some(4))).as_deref(),
+            Some("long")
+        );
+    }
+
+    #[test]
+    fn usernames_follow_the_policy() {
+        for name in ["alice", "a.b-c_d", "Bob99", "abc"] {
+            assert_eq!(username(name), None, "{}", name);
+        }
+        let long = "a".repeat(21);
+        let rejected = [
+            ("ab", "username too short"),
+            (long.as_str(), "username too long"),
+            (
+                "al ice",
+                "username may only contain letters, digits, '_', '.' and '-'",
+            ),
+            (
+                "ålice",
+                "username may only contain letters, digits, '_', '.' and '-'",
+            ),
+            (
+                "_alice",
+                "username must start and end with a letter or digit",
+            ),
+            (
+                "alice.",
+                "username must start and end with a letter or digit",
+            ),
+            (
+                "alice-",
+                "username must start and end with a letter or digit",
+            ),
+            ("Admin", "username is reserved"),
+            ("SUPPORT", "username is reserved"),
+        ];
+        for (name, message) in rejected {
+            assert_eq!(username(name).as_deref(), Some(message), "{}", name);
+        }
+    }
+}
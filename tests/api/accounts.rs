@@ -0,0 +1,177 @@
+use crate::{
+    harness::{TestApp, TestClient, PASSWORD},
+    posts::create_post,
+};
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use server::{accounts, exports};
+use sqlx::PgPool;
+
+async fn log_in(app: &TestApp, username: &str) -> TestClient {
+    let mut client = app.client();
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": username, "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    client
+}
+
+#[sqlx::test]
+async fn deleting_an_account_waits_out_the_grace_period(db: PgPool) {
+    // Purging removes files through tokio, while `sqlx::test` runs on async-std.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _tokio = runtime.enter();
+    let app = TestApp::new(db.clone());
+    let mut alice = app.user("alice").await;
+    let mut elsewhere = log_in(&app, "alice").await;
+    create_post(&mut alice, "Farewell", "Leaving soon").await;
+
+    let response = alice
+        .delete_with("/me", json!({"password": "wrong-horse"}))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.data()["password"], "password is incorrect");
+
+    let response = alice
+        .delete_with("/me", json!({"password": PASSWORD}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let delete_after: DateTime<Utc> = response.data()["delete_after"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let expected = Utc::now() + Duration::days(30);
+    assert!((delete_after - expected).abs() < Duration::minutes(1));
+
+    // Every session of a user pending deletion is refused, not just this one.
+    let response = alice.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    let response = elsewhere.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    assert_eq!(accounts::purge_deleted_accounts(&db).await.unwrap(), 0);
+    sqlx::query("UPDATE users SET delete_after = NOW() - INTERVAL '1 second'")
+        .execute(&db)
+        .await
+        .unwrap();
+    assert_eq!(accounts::purge_deleted_accounts(&db).await.unwrap(), 1);
+    let posts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM posts")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(posts, 0);
+
+    let response = app
+        .client()
+        .post(
+            "/auth/login",
+            json!({"username": "alice", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test]
+async fn logging_in_cancels_a_pending_deletion(db: PgPool) {
+    let app = TestApp::new(db.clone());
+    let mut alice = app.user("alice").await;
+    alice
+        .delete_with("/me", json!({"password": PASSWORD}))
+        .await;
+
+    let mut client = app.client();
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": "alice", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["deletion_cancelled"], true);
+    let response = client.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app
+        .client()
+        .post(
+            "/auth/login",
+            json!({"username": "alice", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.data()["deletion_cancelled"], false);
+
+    let pending: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE delete_after IS NOT NULL")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+    assert_eq!(pending, 0);
+}
+
+#[sqlx::test]
+async fn exports_hold_the_users_own_data(db: PgPool) {
+    // Exports are written by a task spawned on tokio, while `sqlx::test`
+    // runs on async-std.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _tokio = runtime.enter();
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let post_id = create_post(&mut alice, "Mine", "Export me").await;
+    create_post(&mut bob, "Bob's", "Not alice's").await;
+    alice
+        .post(
+            &format!("/posts/{}/comments", post_id),
+            json!({"content": "Self reply"}),
+        )
+        .await;
+    alice
+        .post(
+            &format!("/posts/{}/react", post_id),
+            json!({"is_like": true}),
+        )
+        .await;
+
+    let response = alice.post("/me/export", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let export_id = response.data()["export_id"].as_str().unwrap().to_string();
+    let status = format!("/me/export/{}", export_id);
+    let download = format!("/me/export/{}/download", export_id);
+
+    let response = bob.get(&status).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let mut attempts = 0;
+    loop {
+        let response = alice.get(&status).await;
+        assert_eq!(response.status, StatusCode::OK);
+        match response.data()["export"]["status"].as_str().unwrap() {
+            "complete" => break,
+            "pending" if attempts < 50 => {
+                attempts += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            other => panic!("the export is {}", other),
+        }
+    }
+
+    let response = bob.get(&download).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    let response = alice.get(&download).await;
+    exports::remove_export_file(&format!("{}.json", export_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let archive = &response.body;
+    assert_eq!(archive["user"]["username"], "alice");
+    assert_eq!(archive["user"]["email"], "alice@example.com");
+    assert!(archive["user"].get("password").is_none());
+    let posts = archive["posts"].as_array().unwrap();
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0]["title"], "Mine");
+    assert_eq!(archive["comments"][0]["content"], "Self reply");
+    assert_eq!(archive["reactions"][0]["reaction_type"], true);
+}
@@ -0,0 +1,253 @@
+use crate::harness::{TestApp, PASSWORD};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn login_starts_a_session_and_logout_ends_it(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut client = app.client();
+
+    let response = client
+        .post(
+            "/auth/register",
+            json!({"username": "alice", "email": "alice@example.com", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = client.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": "alice", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["username"], "alice");
+
+    let response = client.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["username"], "alice");
+
+    let response = client.post("/auth/logout", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = client.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test]
+async fn register_rejects_taken_username_and_email(db: PgPool) {
+    let app = TestApp::new(db);
+    app.user("alice").await;
+
+    let response = app
+        .client()
+        .post(
+            "/auth/register",
+            json!({"username": "alice", "email": "alice@example.com", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.data()["username"], "username already exists");
+    assert_eq!(response.data()["email"], "email already exists");
+}
+
+#[sqlx::test]
+async fn register_validates_the_body(db: PgPool) {
+    let app = TestApp::new(db);
+
+    let response = app
+        .client()
+        .post(
+            "/auth/register",
+            json!({"username": "alice", "email": "alice@example.com", "password": "short"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.data().get("password").is_some());
+}
+
+#[sqlx::test]
+async fn login_rejects_bad_credentials(db: PgPool) {
+    let app = TestApp::new(db);
+    app.user("alice").await;
+    let mut client = app.client();
+
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": "alice", "password": "not-the-password"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert!(response.data().get("password").is_some());
+
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": "bob", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert!(response.data().get("username").is_some());
+
+    let response = client.post("/auth/status", json!({})).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test]
+async fn concurrent_registrations_create_one_user(db: PgPool) {
+    let app = TestApp::new(db.clone());
+    let register = |email: &'static str| {
+        let mut client = app.client();
+        async move {
+            client
+                .post(
+                    "/auth/register",
+                    json!({"username": "alice", "email": email, "password": PASSWORD}),
+                )
+                .await
+        }
+    };
+
+    let (first, second) =
+        tokio::join!(register("alice@example.com"), register("alice@example.org"));
+    let mut statuses = [first.status, second.status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+    let conflict = if first.status == StatusCode::CONFLICT {
+        first
+    } else {
+        second
+    };
+    assert_eq!(conflict.data()["username"], "username already exists");
+
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    let profiles: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM profiles")
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!((users, profiles), (1, 1));
+}
+
+#[sqlx::test]
+async fn register_treats_email_case_insensitively(db: PgPool) {
+    let app = TestApp::new(db);
+    app.user("alice").await;
+
+    let response = app
+        .client()
+        .post(
+            "/auth/register",
+            json!({"username": "alice2", "email": "Alice@Example.com", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.data()["email"], "email already exists");
+    assert!(response.data().get("username").is_none());
+}
+
+#[sqlx::test]
+async fn register_enforces_the_username_and_email_policy(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut client = app.client();
+
+    let cases = [
+        (
+            "Admin",
+            "admin@example.com",
+            PASSWORD,
+            "username",
+            "username is reserved",
+        ),
+        (
+            "bob smith",
+            "bob@example.com",
+            PASSWORD,
+            "username",
+            "username may only contain letters, digits, '_', '.' and '-'",
+        ),
+        (
+            "bob.",
+            "bob@example.com",
+            PASSWORD,
+            "username",
+            "username must start and end with a letter or digit",
+        ),
+        (
+            "bob",
+            "not-an-email",
+            PASSWORD,
+            "email",
+            "email is not a valid address",
+        ),
+        (
+            "bob",
+            "bob@example.com",
+            "x",
+            "password",
+            "password too short",
+        ),
+    ];
+    for (username, email, password, field, message) in cases {
+        let response = client
+            .post(
+                "/auth/register",
+                json!({"username": username, "email": email, "password": password}),
+            )
+            .await;
+        assert_eq!(
+            response.status,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{}",
+            username
+        );
+        assert_eq!(response.data()[field], message, "{}", username);
+    }
+
+    let email = format!("{}@example.com", "b".repeat(40));
+    let response = client
+        .post(
+            "/auth/register",
+            json!({"username": "bob", "email": email, "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[sqlx::test]
+async fn legacy_usernames_can_still_log_in(db: PgPool) {
+    let app = TestApp::new(db.clone());
+    app.user("alice").await;
+    // Accounts from before the length policy may have shorter names.
+    sqlx::query("UPDATE users SET username = 'al' WHERE username = 'alice'")
+        .execute(&db)
+        .await
+        .unwrap();
+
+    let mut client = app.client();
+    let response = client
+        .post(
+            "/auth/login",
+            json!({"username": "al", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["username"], "al");
+
+    let response = client
+        .post(
+            "/auth/register",
+            json!({"username": "bo", "email": "bo@example.com", "password": PASSWORD}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["username"], "username too short");
+}
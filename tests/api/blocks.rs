@@ -0,0 +1,137 @@
+use crate::{
+    harness::{TestApp, TestClient},
+    posts::create_post,
+};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+/// The usernames behind the posts in the viewer's feed.
+async fn feed(client: &mut TestClient) -> Vec<Value> {
+    let response = client.get("/posts").await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["username"].clone())
+        .collect()
+}
+
+async fn commenters(client: &mut TestClient, post_id: &str) -> Vec<Value> {
+    let response = client.get(&format!("/posts/{}/comments", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["comments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|comment| comment["username"].clone())
+        .collect()
+}
+
+#[sqlx::test]
+async fn blocking_hides_both_sides_and_stops_interaction(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let alice_post = create_post(&mut alice, "Alice", "From alice").await;
+    let bob_post = create_post(&mut bob, "Bob", "From bob").await;
+
+    let response = alice.post("/user/bob/block", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = alice.get("/me/blocks").await;
+    assert_eq!(response.data()["users"][0]["username"], "bob");
+
+    assert_eq!(feed(&mut alice).await, ["alice"]);
+    assert_eq!(feed(&mut bob).await, ["bob"]);
+    assert_eq!(feed(&mut app.client()).await, ["bob", "alice"]);
+    let response = alice.get(&format!("/posts/{}", bob_post)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = bob
+        .post(
+            &format!("/posts/{}/comments", alice_post),
+            json!({"content": "Hey"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = alice
+        .post(
+            &format!("/posts/{}/react", bob_post),
+            json!({"is_like": true}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = bob
+        .post("/conversations", json!({"usernames": ["alice"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = alice.delete("/user/bob/block").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(feed(&mut alice).await, ["bob", "alice"]);
+    let response = bob
+        .post(
+            &format!("/posts/{}/comments", alice_post),
+            json!({"content": "Hey"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[sqlx::test]
+async fn muting_only_hides_for_the_muter(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let alice_post = create_post(&mut alice, "Alice", "From alice").await;
+    let bob_post = create_post(&mut bob, "Bob", "From bob").await;
+
+    let response = alice.post("/user/bob/mute", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = alice.get("/me/mutes").await;
+    assert_eq!(response.data()["users"][0]["username"], "bob");
+    assert!(alice.get("/me/blocks").await.data()["users"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(feed(&mut alice).await, ["alice"]);
+    assert_eq!(feed(&mut bob).await, ["bob", "alice"]);
+
+    // Unlike a block, a mute stops nobody from interacting.
+    let comments = format!("/posts/{}/comments", alice_post);
+    let response = bob.post(&comments, json!({"content": "Hey"})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = alice
+        .post(
+            &format!("/posts/{}/react", bob_post),
+            json!({"is_like": true}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = bob
+        .post("/conversations", json!({"usernames": ["alice"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    assert!(commenters(&mut alice, &alice_post).await.is_empty());
+    assert_eq!(commenters(&mut bob, &alice_post).await, ["bob"]);
+
+    let response = alice.delete("/user/bob/mute").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(commenters(&mut alice, &alice_post).await, ["bob"]);
+    assert_eq!(feed(&mut alice).await, ["bob", "alice"]);
+}
+
+#[sqlx::test]
+async fn users_cannot_block_themselves_or_nobody(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    let response = alice.post("/user/alice/block", json!({})).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.data()["username"], "cannot target yourself");
+    let response = alice.post("/user/nobody/mute", json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,125 @@
+use crate::{
+    harness::{TestApp, TestClient},
+    posts::create_post,
+};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+/// The titles of the bookmarked posts, most recently bookmarked first.
+async fn bookmarks(client: &mut TestClient, query: &str) -> Vec<Value> {
+    let response = client.get(&format!("/me/bookmarks{}", query)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["title"].clone())
+        .collect()
+}
+
+#[sqlx::test]
+async fn bookmarking_again_moves_the_bookmark(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Keeper", "Worth reading").await;
+    let bookmark = format!("/posts/{}/bookmark", post_id);
+
+    let response = alice.post(&bookmark, json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["collection"], Value::Null);
+    assert_eq!(bookmarks(&mut alice, "").await, ["Keeper"]);
+
+    let response = alice.post(&bookmark, json!({"collection": "later"})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["collection"], "later");
+    assert_eq!(bookmarks(&mut alice, "").await, ["Keeper"]);
+    assert_eq!(bookmarks(&mut alice, "?collection=later").await, ["Keeper"]);
+
+    let response = alice.delete(&bookmark).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(bookmarks(&mut alice, "").await.is_empty());
+    let response = alice.delete(&bookmark).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.data()["bookmark"], "bookmark does not exist");
+}
+
+#[sqlx::test]
+async fn bookmarks_are_filtered_by_collection(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    for (title, collection) in [
+        ("Recipe", json!("food")),
+        ("Essay", json!("reading")),
+        ("Stew", json!("food")),
+        ("Loose", Value::Null),
+    ] {
+        let post_id = create_post(&mut alice, title, "Content").await;
+        let response = alice
+            .post(
+                &format!("/posts/{}/bookmark", post_id),
+                json!({"collection": collection}),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
+
+    assert_eq!(
+        bookmarks(&mut alice, "").await,
+        ["Loose", "Stew", "Essay", "Recipe"]
+    );
+    assert_eq!(
+        bookmarks(&mut alice, "?collection=food").await,
+        ["Stew", "Recipe"]
+    );
+    assert!(bookmarks(&mut alice, "?collection=none").await.is_empty());
+    assert_eq!(
+        bookmarks(&mut alice, "?per_page=2&page=2").await,
+        ["Essay", "Recipe"]
+    );
+    // Bookmarks are private to their owner.
+    assert!(bookmarks(&mut bob, "").await.is_empty());
+}
+
+#[sqlx::test]
+async fn only_live_posts_can_be_bookmarked(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Fleeting", "Gone soon").await;
+    let bookmark = format!("/posts/{}/bookmark", post_id);
+    alice.post(&bookmark, json!({})).await;
+
+    let response = alice
+        .post(&bookmark, json!({"collection": "x".repeat(51)}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["collection"], "collection too long");
+
+    alice.delete(&format!("/posts/{}", post_id)).await;
+    assert!(bookmarks(&mut alice, "").await.is_empty());
+    let response = alice.post(&bookmark, json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = alice
+        .post(
+            "/posts/00000000-0000-0000-0000-000000000000/bookmark",
+            json!({}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+async fn pages_past_the_limit_are_rejected(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    let response = alice.get("/me/bookmarks?page=10000&per_page=100").await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = alice
+        .get("/me/bookmarks?page=9223372036854775807&per_page=100")
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["page"], "page must be between 1 and 10000");
+}
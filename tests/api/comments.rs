@@ -0,0 +1,68 @@
+use crate::{harness::TestApp, posts::create_post};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn comments_are_listed_and_counted(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let post_id = create_post(&mut alice, "Question", "Anyone around?").await;
+
+    let response = bob
+        .post(
+            &format!("/posts/{}/comments", post_id),
+            json!({"content": "I am, @alice"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let comment_id = response.data()["comment_id"].as_str().unwrap().to_string();
+
+    let response = app
+        .client()
+        .get(&format!("/posts/{}/comments", post_id))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let comments = response.data()["comments"].as_array().unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0]["id"], comment_id.as_str());
+    assert_eq!(comments[0]["username"], "bob");
+    assert_eq!(comments[0]["content"], "I am, @alice");
+    assert_eq!(comments[0]["mentions"][0]["username"], "alice");
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["comments"], 1);
+}
+
+#[sqlx::test]
+async fn commenting_requires_a_session_and_an_existing_post(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Quiet", "Nobody here").await;
+
+    let response = app
+        .client()
+        .post(
+            &format!("/posts/{}/comments", post_id),
+            json!({"content": "Anonymous"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = alice
+        .post(
+            "/posts/00000000-0000-0000-0000-000000000000/comments",
+            json!({"content": "Hello?"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = alice
+        .post(
+            &format!("/posts/{}/comments", post_id),
+            json!({"content": ""}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,125 @@
+use crate::{harness::TestApp, posts::create_post};
+use serde_json::json;
+use server::shutdown;
+use sqlx::PgPool;
+use std::{future::IntoFuture, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+#[tokio::test]
+async fn an_open_event_stream_does_not_hold_up_shutdown() {
+    let app = TestApp::in_memory();
+    let alice = app.user("alice").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(
+        axum::serve(listener, app.router())
+            .with_graceful_shutdown(shutdown::requested(app.shutdown_requested()))
+            .into_future(),
+    );
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let request = format!(
+        "GET /api/v1/events HTTP/1.1\r\nHost: {}\r\nCookie: {}\r\n\r\n",
+        address,
+        alice.cookie().unwrap()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut buffer = [0; 1024];
+    let read = stream.read(&mut buffer).await.unwrap();
+    let head = String::from_utf8_lossy(&buffer[..read]);
+    assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+    assert!(head.contains("text/event-stream"), "{}", head);
+
+    app.shutdown();
+    let served = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("the server stops without waiting for the stream");
+    served.unwrap().unwrap();
+
+    let mut rest = Vec::new();
+    stream.read_to_end(&mut rest).await.unwrap();
+}
+
+#[sqlx::test]
+async fn comments_by_muted_users_are_left_out_of_the_stream(db: PgPool) {
+    // `sqlx::test` runs on async-std, while the stream needs tokio timers.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _tokio = runtime.enter();
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let mut carol = app.user("carol").await;
+    let post_id = create_post(&mut alice, "Question", "Anyone around?").await;
+    let comments = format!("/posts/{}/comments", post_id);
+
+    let mut events = alice.events(&format!("/events?posts={}", post_id)).await;
+    // Muting after connecting checks that the stream picks up the change.
+    alice.post("/user/bob/mute", json!({})).await;
+    bob.post(&comments, json!({"content": "Me!"})).await;
+    carol.post(&comments, json!({"content": "I am"})).await;
+
+    loop {
+        let (name, data) = events.next().await;
+        if name == "comment" {
+            assert_eq!(data["data"]["username"], "carol");
+            break;
+        }
+    }
+}
+
+#[sqlx::test]
+async fn posts_hidden_from_the_viewer_are_not_streamed(db: PgPool) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _tokio = runtime.enter();
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let mut carol = app.user("carol").await;
+    let mut dave = app.user("dave").await;
+    let alice_post = create_post(&mut alice, "Blocker", "Not for bob").await;
+    let carol_post = create_post(&mut carol, "Open", "For everyone").await;
+    let deleted_post = create_post(&mut carol, "Gone", "Soon").await;
+    carol.delete(&format!("/posts/{}", deleted_post)).await;
+    alice.post("/user/bob/block", json!({})).await;
+
+    let watch = format!(
+        "/events?posts={},{},{}",
+        alice_post, deleted_post, carol_post
+    );
+    let mut bob_events = bob.events(&watch).await;
+    let mut carol_events = carol.events(&watch).await;
+    // Blocking after connecting stops the post's events too.
+    alice.post("/user/carol/block", json!({})).await;
+
+    dave.post(
+        &format!("/posts/{}/react", alice_post),
+        json!({"is_like": true}),
+    )
+    .await;
+    dave.post(
+        &format!("/posts/{}/comments", alice_post),
+        json!({"content": "Hidden"}),
+    )
+    .await;
+    dave.post(
+        &format!("/posts/{}/comments", carol_post),
+        json!({"content": "Shown"}),
+    )
+    .await;
+
+    for events in [&mut bob_events, &mut carol_events] {
+        loop {
+            let (name, data) = events.next().await;
+            if name == "notification" {
+                continue;
+            }
+            assert_eq!(name, "comment");
+            assert_eq!(data["data"]["post_id"], carol_post.as_str());
+            break;
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use axum::{
+    body::{to_bytes, Body, BodyDataStream},
+    http::{
+        header::{CONTENT_TYPE, COOKIE, SET_COOKIE},
+        Method, Request, StatusCode,
+    },
+    middleware, Router,
+};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use server::{
+    config::Config, events::EventBus, health::Readiness, metrics, repo::Repos, request_id,
+    route::create_router, AppState,
+};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::watch;
+use tower::ServiceExt;
+use tower_sessions::{MemoryStore, SessionManagerLayer};
+
+const PREFIX: &str = "/api/v1";
+pub const PASSWORD: &str = "correct-horse";
+
+/// The application as `main` assembles it, with sessions kept in memory
+/// instead of Redis and no Redis pool or event subscriber.
+#[derive(Clone)]
+pub struct TestApp {
+    router: Router,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl TestApp {
+    pub fn new(db: PgPool) -> Self {
+        let repos = Repos::postgres(db.clone());
+        TestApp::with_repos(db, repos)
+    }
+
+    /// The application with in-memory repositories. Routes that still query
+    /// the database directly fail, since the pool never connects.
+    pub fn in_memory() -> Self {
+        let db = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blaze")
+            .expect("the URL is valid");
+        TestApp::with_repos(db, Repos::in_memory())
+    }
+
+    fn with_repos(db: PgPool, repos: Repos) -> Self {
+        let config = Config::load(
+            |key| (key == "DATABASE_URL").then(|| "postgres://localhost/blaze".to_string()),
+            toml::Table::new(),
+        )
+        .expect("the default configuration is valid");
+
+        let (shutdown, shutdown_requested) = watch::channel(false);
+        let router = create_router(Arc::new(AppState {
+            db,
+            repos,
+            env: config,
+            events: EventBus::new(None),
+            redis: None,
+            readiness: Readiness::default(),
+            metrics: metrics::detached(),
+            shutdown: shutdown_requested,
+        }))
+        .layer(SessionManagerLayer::new(MemoryStore::default()).with_secure(false))
+        .layer(middleware::from_fn(request_id::request_id));
+
+        TestApp {
+            router,
+            shutdown: Arc::new(shutdown),
+        }
+    }
+
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    pub fn shutdown_requested(&self) -> watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
+    /// Starts shutting down, like SIGTERM does in `main`.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// A client without a session.
+    pub fn client(&self) -> TestClient {
+        TestClient {
+            router: self.router.clone(),
+            cookie: None,
+        }
+    }
+
+    /// A client logged in as a freshly registered user.
+    pub async fn user(&self, username: &str) -> TestClient {
+        let mut client = self.client();
+        let response = client
+            .post(
+                "/auth/register",
+                json!({
+                    "username": username,
+                    "email": format!("{}@example.com", username),
+                    "password": PASSWORD,
+                }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+        let response = client
+            .post(
+                "/auth/login",
+                json!({"username": username, "password": PASSWORD}),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        client
+    }
+}
+
+/// Sends requests to `/api/v1` and keeps the session cookie between them,
+/// like a browser would.
+pub struct TestClient {
+    router: Router,
+    cookie: Option<String>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub body: Value,
+}
+
+pub struct EventStream {
+    body: BodyDataStream,
+    buffer: String,
+}
+
+impl EventStream {
+    /// The name and data of the next event, skipping keep-alives. Panics if
+    /// none arrives within a few seconds.
+    pub async fn next(&mut self) -> (String, Value) {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                let mut name = String::new();
+                let mut data = Value::Null;
+                for line in block.lines() {
+                    if let Some(value) = line.strip_prefix("event:") {
+                        name = value.trim().to_string();
+                    } else if let Some(value) = line.strip_prefix("data:") {
+                        data = serde_json::from_str(value.trim()).unwrap();
+                    }
+                }
+                if !name.is_empty() {
+                    return (name, data);
+                }
+                continue;
+            }
+            let chunk = tokio::time::timeout(Duration::from_secs(5), self.body.next())
+                .await
+                .expect("an event arrives")
+                .expect("the stream is open")
+                .unwrap();
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
+
+impl TestResponse {
+    /// The `data` of a JSend response.
+    pub fn data(&self) -> &Value {
+        &self.body["data"]
+    }
+}
+
+impl TestClient {
+    /// The session cookie, as sent in the `Cookie` header.
+    pub fn cookie(&self) -> Option<&str> {
+        self.cookie.as_deref()
+    }
+
+    pub async fn get(&mut self, path: &str) -> TestResponse {
+        self.send(Method::GET, path, None).await
+    }
+
+    pub async fn post(&mut self, path: &str, body: Value) -> TestResponse {
+        self.send(Method::POST, path, Some(body)).await
+    }
+
+    pub async fn patch(&mut self, path: &str, body: Value) -> TestResponse {
+        self.send(Method::PATCH, path, Some(body)).await
+    }
+
+    pub async fn delete(&mut self, path: &str) -> TestResponse {
+        self.send(Method::DELETE, path, None).await
+    }
+
+    /// Deletes with a JSON body, such as the password closing an account.
+    pub async fn delete_with(&mut self, path: &str, body: Value) -> TestResponse {
+        self.send(Method::DELETE, path, Some(body)).await
+    }
+
+    /// Opens a server-sent event stream. Keep-alives run on tokio timers, so
+    /// this needs a tokio runtime context.
+    pub async fn events(&mut self, path: &str) -> EventStream {
+        let mut request = Request::builder().uri(format!("{}{}", PREFIX, path));
+        if let Some(cookie) = &self.cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        let request = request.body(Body::empty()).unwrap();
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        EventStream {
+            body: response.into_body().into_data_stream(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Posts a raw body, such as a multipart upload.
+    pub async fn upload(&mut self, path: &str, content_type: &str, body: Vec<u8>) -> TestResponse {
+        self.send_body(Method::POST, path, Some(content_type), Body::from(body))
+            .await
+    }
+
+    async fn send(&mut self, method: Method, path: &str, body: Option<Value>) -> TestResponse {
+        match body {
+            Some(body) => {
+                let body = Body::from(body.to_string());
+                self.send_body(method, path, Some("application/json"), body)
+                    .await
+            }
+            None => self.send_body(method, path, None, Body::empty()).await,
+        }
+    }
+
+    async fn send_body(
+        &mut self,
+        method: Method,
+        path: &str,
+        content_type: Option<&str>,
+        body: Body,
+    ) -> TestResponse {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", PREFIX, path));
+        if let Some(cookie) = &self.cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        let request = request.body(body).unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+
+        if let Some(set_cookie) = response
+            .headers()
+            .get(SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+        {
+            let expired = set_cookie.contains("Max-Age=0");
+            let cookie = set_cookie.split(';').next().unwrap_or_default();
+            self.cookie = (!expired).then(|| cookie.to_string());
+        }
+
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap()
+        };
+        TestResponse { status, body }
+    }
+}
@@ -0,0 +1,65 @@
+//! The same flows against the in-memory repositories, which need no database.
+
+use crate::{
+    harness::TestApp,
+    posts::{create_post, writes_only_touch_live_posts},
+};
+use axum::http::StatusCode;
+use serde_json::json;
+use server::repo::Repos;
+
+#[tokio::test]
+async fn posts_comments_and_reactions_without_a_database() {
+    let app = TestApp::in_memory();
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let post_id = create_post(&mut alice, "Offline", "Hello @bob").await;
+
+    let response = bob
+        .post(
+            &format!("/posts/{}/comments", post_id),
+            json!({"content": "Hi @alice"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = bob
+        .post(
+            &format!("/posts/{}/react", post_id),
+            json!({"is_like": true}),
+        )
+        .await;
+    assert_eq!(response.data()["like_count"], 1);
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    let post = &response.data()["post"];
+    assert_eq!(post["username"], "alice");
+    assert_eq!(post["likes"], 1);
+    assert_eq!(post["comments"], 1);
+    assert_eq!(post["mentions"][0]["username"], "bob");
+
+    let response = alice.delete(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.client().get("/posts").await;
+    assert_eq!(response.data()["posts"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn register_conflicts_without_a_database() {
+    let app = TestApp::in_memory();
+    app.user("alice").await;
+
+    let response = app
+        .client()
+        .post(
+            "/auth/register",
+            json!({"username": "alice", "email": "alice@example.com", "password": "correct-horse"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn post_writes_check_the_author_and_deletion_without_a_database() {
+    writes_only_touch_live_posts(Repos::in_memory()).await;
+}
@@ -0,0 +1,19 @@
+//! End-to-end tests of the HTTP API. Each test gets its own database with
+//! every migration applied, created by `sqlx::test` from `DATABASE_URL`;
+//! the `in_memory` tests run against the in-memory repositories instead, and
+//! the `seed` tests call the seeder directly.
+
+mod accounts;
+mod auth;
+mod blocks;
+mod bookmarks;
+mod comments;
+mod events;
+mod harness;
+mod in_memory;
+mod messages;
+mod notifications;
+mod posts;
+mod profiles;
+mod reactions;
+mod seed;
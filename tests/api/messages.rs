@@ -0,0 +1,176 @@
+use crate::harness::{TestApp, TestClient};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+async fn start_conversation(client: &mut TestClient, usernames: &[&str]) -> String {
+    let response = client
+        .post("/conversations", json!({"usernames": usernames}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    response.data()["conversation_id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn send(client: &mut TestClient, conversation_id: &str, content: &str) -> StatusCode {
+    client
+        .post(
+            &format!("/conversations/{}/messages", conversation_id),
+            json!({"content": content}),
+        )
+        .await
+        .status
+}
+
+async fn unread_count(client: &mut TestClient) -> i64 {
+    let response = client.get("/conversations/unread_count").await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["unread_count"].as_i64().unwrap()
+}
+
+#[sqlx::test]
+async fn one_to_one_conversations_are_reused(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    app.user("carol").await;
+
+    let direct = start_conversation(&mut alice, &["bob"]).await;
+    assert_eq!(start_conversation(&mut bob, &["alice"]).await, direct);
+    assert_eq!(
+        start_conversation(&mut alice, &["bob", "bob", "alice"]).await,
+        direct
+    );
+
+    let group = start_conversation(&mut alice, &["bob", "carol"]).await;
+    assert_ne!(group, direct);
+    assert_ne!(
+        start_conversation(&mut alice, &["bob", "carol"]).await,
+        group
+    );
+
+    let response = alice
+        .post("/conversations", json!({"usernames": ["alice"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["usernames"], "add at least one other user");
+    let response = alice
+        .post("/conversations", json!({"usernames": ["nobody"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["usernames"], "user nobody does not exist");
+}
+
+#[sqlx::test]
+async fn only_members_see_or_write_to_a_conversation(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut carol = app.user("carol").await;
+    app.user("bob").await;
+    let conversation_id = start_conversation(&mut alice, &["bob"]).await;
+    assert_eq!(
+        send(&mut alice, &conversation_id, "Just us").await,
+        StatusCode::OK
+    );
+
+    let messages = format!("/conversations/{}/messages", conversation_id);
+    let response = carol.get(&messages).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        send(&mut carol, &conversation_id, "Let me in").await,
+        StatusCode::NOT_FOUND
+    );
+    let response = carol
+        .post(
+            &format!("/conversations/{}/read", conversation_id),
+            json!({}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = carol.get("/conversations/not-a-uuid/messages").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = carol.get("/conversations").await;
+    assert!(response.data()["conversations"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let response = alice.get(&messages).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let list = response.data()["messages"].as_array().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["content"], "Just us");
+    assert_eq!(list[0]["username"], "alice");
+}
+
+#[sqlx::test]
+async fn unread_counts_follow_reading_and_replying(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let conversation_id = start_conversation(&mut bob, &["alice"]).await;
+
+    send(&mut bob, &conversation_id, "Hi").await;
+    send(&mut bob, &conversation_id, "Are you there?").await;
+    assert_eq!(unread_count(&mut alice).await, 2);
+    assert_eq!(unread_count(&mut bob).await, 0);
+
+    let response = alice.get("/conversations").await;
+    let conversations = response.data()["conversations"].as_array().unwrap();
+    assert_eq!(conversations.len(), 1);
+    assert_eq!(conversations[0]["unread_count"], 2);
+    assert_eq!(conversations[0]["last_message"], "Are you there?");
+    assert_eq!(conversations[0]["members"].as_array().unwrap().len(), 2);
+
+    let response = alice
+        .post(
+            &format!("/conversations/{}/read", conversation_id),
+            json!({}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(unread_count(&mut alice).await, 0);
+
+    // Replying marks the conversation read for the sender.
+    send(&mut bob, &conversation_id, "Hello?").await;
+    send(&mut alice, &conversation_id, "Here").await;
+    assert_eq!(unread_count(&mut alice).await, 0);
+    assert_eq!(unread_count(&mut bob).await, 1);
+}
+
+#[sqlx::test]
+async fn blocked_users_cannot_message_each_other(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let mut carol = app.user("carol").await;
+    let direct = start_conversation(&mut alice, &["bob"]).await;
+    let group = start_conversation(&mut carol, &["alice", "bob"]).await;
+
+    let response = alice.post("/user/bob/block", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    // Both sides are stopped, whoever did the blocking.
+    for client in [&mut alice, &mut bob] {
+        assert_eq!(send(client, &direct, "Hi").await, StatusCode::FORBIDDEN);
+        assert_eq!(send(client, &group, "Hi").await, StatusCode::FORBIDDEN);
+    }
+    let response = bob
+        .post("/conversations", json!({"usernames": ["alice"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.data()["usernames"], "cannot message alice");
+    let response = alice
+        .post("/conversations", json!({"usernames": ["bob", "carol"]}))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    // Others in the group can still talk.
+    assert_eq!(send(&mut carol, &group, "Hi all").await, StatusCode::OK);
+
+    let response = alice.delete("/user/bob/block").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(send(&mut bob, &direct, "Thanks").await, StatusCode::OK);
+}
@@ -0,0 +1,136 @@
+use crate::{
+    harness::{TestApp, TestClient},
+    posts::create_post,
+};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+async fn notifications(client: &mut TestClient, query: &str) -> Vec<Value> {
+    let response = client.get(&format!("/notifications{}", query)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["notifications"].as_array().unwrap().clone()
+}
+
+async fn unread_count(client: &mut TestClient) -> i64 {
+    let response = client.get("/notifications/unread_count").await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.data()["unread_count"].as_i64().unwrap()
+}
+
+#[sqlx::test]
+async fn likes_are_grouped_until_read(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Grouped", "Like this").await;
+    let react = format!("/posts/{}/react", post_id);
+
+    for name in ["bob", "carol"] {
+        let response = app
+            .user(name)
+            .await
+            .post(&react, json!({"is_like": true}))
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
+    // Liking your own post notifies nobody.
+    alice.post(&react, json!({"is_like": true})).await;
+
+    let list = notifications(&mut alice, "").await;
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["kind"], "like");
+    assert_eq!(list[0]["actor_username"], "carol");
+    assert_eq!(list[0]["actor_count"], 2);
+    assert_eq!(list[0]["is_read"], false);
+    assert_eq!(unread_count(&mut alice).await, 1);
+
+    let read = format!("/notifications/{}/read", list[0]["id"].as_str().unwrap());
+    let response = alice.post(&read, json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(unread_count(&mut alice).await, 0);
+
+    // A like after the group was read starts a new one.
+    app.user("dave")
+        .await
+        .post(&react, json!({"is_like": true}))
+        .await;
+    let list = notifications(&mut alice, "").await;
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0]["actor_username"], "dave");
+    assert_eq!(list[0]["actor_count"], 1);
+    assert_eq!(
+        notifications(&mut alice, "?unread_only=true").await.len(),
+        1
+    );
+    assert_eq!(unread_count(&mut alice).await, 1);
+}
+
+#[sqlx::test]
+async fn comments_and_mentions_notify_everyone_but_the_actor(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let mut carol = app.user("carol").await;
+    let post_id = create_post(&mut alice, "Hello", "Note to self @alice").await;
+    assert!(notifications(&mut alice, "").await.is_empty());
+
+    let comments = format!("/posts/{}/comments", post_id);
+    let response = bob
+        .post(&comments, json!({"content": "Agreed, @carol @bob"}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    bob.post(&comments, json!({"content": "And another thing"}))
+        .await;
+    alice.post(&comments, json!({"content": "Thanks"})).await;
+
+    let list = notifications(&mut alice, "").await;
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["kind"], "comment");
+    assert_eq!(list[0]["actor_username"], "bob");
+    assert_eq!(list[0]["actor_count"], 1);
+
+    let list = notifications(&mut carol, "").await;
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["kind"], "mention");
+    assert_eq!(list[0]["post_id"], post_id.as_str());
+    assert!(!list[0]["comment_id"].is_null());
+
+    assert!(notifications(&mut bob, "").await.is_empty());
+}
+
+#[sqlx::test]
+async fn reading_all_and_reading_someone_elses(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    for title in ["One", "Two"] {
+        let post_id = create_post(&mut alice, title, "Like it").await;
+        bob.post(
+            &format!("/posts/{}/react", post_id),
+            json!({"is_like": true}),
+        )
+        .await;
+    }
+    assert_eq!(unread_count(&mut alice).await, 2);
+
+    let id = notifications(&mut alice, "").await[0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response = bob
+        .post(&format!("/notifications/{}/read", id), json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = bob.post("/notifications/not-a-uuid/read", json!({})).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unread_count(&mut alice).await, 2);
+
+    let response = alice.post("/notifications/read_all", json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["marked_read"], 2);
+    assert_eq!(unread_count(&mut alice).await, 0);
+    assert!(notifications(&mut alice, "?unread_only=true")
+        .await
+        .is_empty());
+    assert_eq!(notifications(&mut alice, "").await.len(), 2);
+}
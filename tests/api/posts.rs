@@ -0,0 +1,276 @@
+use crate::harness::{TestApp, TestClient};
+use axum::http::StatusCode;
+use serde_json::json;
+use server::repo::{NewUser, Repos};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_post(client: &mut TestClient, title: &str, content: &str) -> String {
+    let response = client
+        .post("/posts", json!({"title": title, "content": content}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    response.data()["post_id"].as_str().unwrap().to_string()
+}
+
+#[sqlx::test]
+async fn post_lifecycle(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    let post_id = create_post(&mut alice, "First post", "Hello #rust").await;
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let post = &response.data()["post"];
+    assert_eq!(post["title"], "First post");
+    assert_eq!(post["content"], "Hello #rust");
+    assert_eq!(post["username"], "alice");
+
+    let response = app.client().get("/posts").await;
+    assert_eq!(response.status, StatusCode::OK);
+    let posts = response.data()["posts"].as_array().unwrap();
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0]["id"], post_id.as_str());
+
+    let response = alice
+        .patch(
+            &format!("/posts/{}", post_id),
+            json!({"title": "Edited post", "content": "Hello again"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["title"], "Edited post");
+    assert_eq!(response.data()["post"]["content"], "Hello again");
+
+    let response = alice.delete(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.client().get("/posts").await;
+    assert!(response.data()["posts"].as_array().unwrap().is_empty());
+}
+
+#[sqlx::test]
+async fn only_the_author_can_edit_or_delete(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let post_id = create_post(&mut alice, "Mine", "Hands off").await;
+
+    let response = bob
+        .patch(
+            &format!("/posts/{}", post_id),
+            json!({"title": "Yours", "content": "Not anymore"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = bob.delete(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["title"], "Mine");
+}
+
+/// Edits and deletions check the author and deleted state themselves, so a
+/// post deleted after the handler looked it up is left alone.
+pub async fn writes_only_touch_live_posts(repos: Repos) {
+    let user = |username: &'static str| {
+        let repos = &repos;
+        async move {
+            let email = format!("{}@example.com", username);
+            repos
+                .users
+                .create(NewUser {
+                    username,
+                    email: &email,
+                    password_hash: "unused",
+                })
+                .await
+                .unwrap()
+        }
+    };
+    let alice = user("alice").await;
+    let bob = user("bob").await;
+    let (post_id, _) = repos.posts.create(alice, "Mine", "Hello").await.unwrap();
+
+    let edited = repos
+        .posts
+        .update(post_id, bob, "Yours", "Hi")
+        .await
+        .unwrap();
+    assert!(edited.is_none());
+    let edited = repos
+        .posts
+        .update(Uuid::new_v4(), alice, "Gone", "Hi")
+        .await
+        .unwrap();
+    assert!(edited.is_none());
+
+    assert!(repos.posts.soft_delete(post_id, bob).await.unwrap());
+    assert!(!repos.posts.soft_delete(post_id, alice).await.unwrap());
+    let deleted = repos
+        .posts
+        .find_deleted(post_id, 30)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deleted.deleted_by, Some(bob));
+
+    let edited = repos
+        .posts
+        .update(post_id, alice, "Back", "Hi")
+        .await
+        .unwrap();
+    assert!(edited.is_none());
+
+    repos.posts.restore(post_id).await.unwrap();
+    let edited = repos
+        .posts
+        .update(post_id, alice, "Back", "Hi")
+        .await
+        .unwrap();
+    assert!(edited.is_some());
+    let post = repos.posts.find(post_id, None).await.unwrap().unwrap();
+    assert_eq!(post.title, "Back");
+}
+
+#[sqlx::test]
+async fn post_writes_check_the_author_and_deletion(db: PgPool) {
+    writes_only_touch_live_posts(Repos::postgres(db)).await;
+}
+
+#[sqlx::test]
+async fn creating_a_post_requires_a_session(db: PgPool) {
+    let app = TestApp::new(db);
+
+    let response = app
+        .client()
+        .post("/posts", json!({"title": "Anonymous", "content": "Hi"}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test]
+async fn post_validation_and_missing_posts(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    let response = alice
+        .post("/posts", json!({"title": "", "content": "No title"}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app
+        .client()
+        .get("/posts/00000000-0000-0000-0000-000000000000")
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.client().get("/posts/not-a-uuid").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = alice.get("/me/bookmarks?page=first").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["status"], "fail");
+    assert!(response.data()["query"].is_string());
+
+    let response = alice
+        .post("/posts", json!({"title": 5, "content": "Numbered"}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["status"], "fail");
+    let reason = response.data()["body"].as_str().unwrap_or_default();
+    assert!(
+        reason.contains("invalid type: integer `5`"),
+        "{}",
+        response.body
+    );
+}
+
+#[sqlx::test]
+async fn post_lengths_are_counted_in_graphemes(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    let title = "🦀".repeat(50);
+    let content = "é".repeat(400);
+    let post_id = create_post(&mut alice, &title, &content).await;
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["title"], title.as_str());
+
+    let response = alice
+        .post("/posts", json!({"title": "🦀".repeat(51), "content": "x"}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["title"], "title too long");
+
+    // Fifty graphemes, but a hundred code points: too long for the column.
+    let response = alice
+        .post(
+            "/posts",
+            json!({"title": "e\u{301}".repeat(50), "content": "x"}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.data()["title"], "title too long");
+}
+
+#[sqlx::test]
+async fn authors_restore_their_posts_within_the_retention_period(db: PgPool) {
+    let app = TestApp::new(db.clone());
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Oops", "Deleted by mistake").await;
+    let restore = format!("/posts/{}/restore", post_id);
+
+    let response = alice.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    alice.delete(&format!("/posts/{}", post_id)).await;
+    let response = alice.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["post_id"], post_id.as_str());
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["title"], "Oops");
+
+    alice.delete(&format!("/posts/{}", post_id)).await;
+    // Past the default 30 days the post only waits to be purged.
+    sqlx::query("UPDATE posts SET deleted_at = NOW() - INTERVAL '31 days' WHERE id = $1::UUID")
+        .bind(&post_id)
+        .execute(&db)
+        .await
+        .unwrap();
+    let response = alice.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.data()["post"], "no deleted post to restore");
+}
+
+#[sqlx::test]
+async fn authors_cannot_undo_a_moderators_deletion(db: PgPool) {
+    let app = TestApp::new(db.clone());
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let mut moderator = app.user("moderator1").await;
+    sqlx::query("UPDATE users SET role = 'moderator' WHERE username = 'moderator1'")
+        .execute(&db)
+        .await
+        .unwrap();
+    let post_id = create_post(&mut alice, "Rude", "Something rude").await;
+    let restore = format!("/posts/{}/restore", post_id);
+
+    let response = moderator.delete(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = alice.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = bob.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = moderator.post(&restore, json!({})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
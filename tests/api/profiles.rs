@@ -0,0 +1,22 @@
+use crate::harness::TestApp;
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn a_malformed_upload_is_a_bad_request(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+
+    // The part headers never end, so the body cannot be parsed.
+    let body = b"--boundary\r\nContent-Disposition: form-data; name=\"file\"".to_vec();
+    let response = alice
+        .upload(
+            "/profile/upload",
+            "multipart/form-data; boundary=boundary",
+            body,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["status"], "fail");
+    assert!(response.data()["file"].is_string(), "{}", response.body);
+}
@@ -0,0 +1,54 @@
+use crate::{harness::TestApp, posts::create_post};
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn reacting_again_replaces_the_reaction(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let mut bob = app.user("bob").await;
+    let post_id = create_post(&mut alice, "Opinions", "Tabs over spaces").await;
+    let react = format!("/posts/{}/react", post_id);
+
+    let response = bob.post(&react, json!({"is_like": true})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["like_count"], 1);
+    assert_eq!(response.data()["dislike_count"], 0);
+
+    let response = alice.post(&react, json!({"is_like": true})).await;
+    assert_eq!(response.data()["like_count"], 2);
+
+    let response = bob.post(&react, json!({"is_like": false})).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.data()["like_count"], 1);
+    assert_eq!(response.data()["dislike_count"], 1);
+
+    let response = app.client().get(&format!("/posts/{}", post_id)).await;
+    assert_eq!(response.data()["post"]["likes"], 1);
+    assert_eq!(response.data()["post"]["dislikes"], 1);
+}
+
+#[sqlx::test]
+async fn reacting_requires_a_session_and_an_existing_post(db: PgPool) {
+    let app = TestApp::new(db);
+    let mut alice = app.user("alice").await;
+    let post_id = create_post(&mut alice, "Opinions", "Tabs over spaces").await;
+
+    let response = app
+        .client()
+        .post(
+            &format!("/posts/{}/react", post_id),
+            json!({"is_like": true}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = alice
+        .post(
+            "/posts/00000000-0000-0000-0000-000000000000/react",
+            json!({"is_like": true}),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,75 @@
+//! The seeder, run straight against the database.
+
+use chrono::{DateTime, Utc};
+use server::seed::{self, SeedOptions};
+use sqlx::PgPool;
+
+/// The seeded rows, leaving out the ids and timestamps the database assigns.
+const SNAPSHOTS: &[&str] = &[
+    "SELECT id, username, email FROM users ORDER BY id",
+    "SELECT user_id, bio FROM profiles ORDER BY user_id",
+    "SELECT id, user_id, title, content, like_count, comment_count FROM posts ORDER BY id",
+    "SELECT id, user_id, post_id, content FROM comments ORDER BY id",
+    "SELECT user_id, post_id, reaction_type FROM reactions ORDER BY post_id, user_id",
+    "SELECT post_tags.post_id, tags.name FROM post_tags
+        JOIN tags ON post_tags.tag_id = tags.id ORDER BY post_id, name",
+    "SELECT user_id, post_id, comment_id, start_offset FROM mentions
+        ORDER BY user_id, post_id, comment_id, start_offset",
+    "SELECT user_id, post_id, kind, actor_id, comment_id FROM notifications
+        ORDER BY user_id, post_id, kind, comment_id",
+];
+
+/// Every timestamp the seeder picks itself.
+const TIMESTAMPS: &str = "SELECT created_at FROM users
+    UNION ALL SELECT created_at FROM posts
+    UNION ALL SELECT created_at FROM comments
+    UNION ALL SELECT created_at FROM notifications";
+
+async fn snapshot(db: &PgPool) -> (Vec<String>, Vec<DateTime<Utc>>) {
+    let mut rows = Vec::new();
+    for query in SNAPSHOTS {
+        let json: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT row_to_json(seeded)::TEXT FROM ({}) AS seeded",
+            query
+        ))
+        .fetch_all(db)
+        .await
+        .unwrap();
+        rows.extend(json);
+    }
+    let mut timestamps: Vec<DateTime<Utc>> =
+        sqlx::query_scalar(TIMESTAMPS).fetch_all(db).await.unwrap();
+    timestamps.sort();
+    (rows, timestamps)
+}
+
+#[sqlx::test]
+async fn the_same_seed_generates_the_same_rows(db: PgPool) {
+    let options = SeedOptions {
+        seed: 7,
+        users: 20,
+        posts: 30,
+        comments_per_post: 3,
+        reactions_per_post: 4,
+        password: "correct-horse".to_string(),
+    };
+
+    let summary = seed::seed(&db, &options).await.unwrap();
+    assert_eq!((summary.users, summary.posts), (20, 30));
+    let (first, first_times) = snapshot(&db).await;
+
+    sqlx::query("TRUNCATE users, tags CASCADE")
+        .execute(&db)
+        .await
+        .unwrap();
+    seed::seed(&db, &options).await.unwrap();
+    let (second, second_times) = snapshot(&db).await;
+
+    assert_eq!(first, second);
+    // Timestamps keep their spacing and move with the time of the run.
+    let shift = second_times[0] - first_times[0];
+    assert_eq!(first_times.len(), second_times.len());
+    for (first, second) in first_times.iter().zip(&second_times) {
+        assert_eq!(*second - *first, shift);
+    }
+}